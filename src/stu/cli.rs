@@ -0,0 +1,1671 @@
+use crate::stu::{
+    self,
+    config::Config,
+    messages::{self, text, text_with},
+    utils::{self, *},
+    Granularity, Journal, JournalSort, JournalStore, LogFilter, Mean, SortField, SortKey, StuError,
+    TableFormat, TableStyle,
+};
+use colored::Colorize;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::result::Result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Help,
+    Version,
+    Show(ShowOptions),
+    Get(Query),
+    AddJournal {
+        name: String,
+        goal: Option<f32>,
+    },
+    AddLogs {
+        // Falls back to the configured default journal when missing
+        journal: Option<String>,
+        source: LogSource,
+        // Already `YYYY-MM-DD`, to back-fill past sessions, today when missing
+        date: Option<String>,
+        // Prints the new logs once saved, `open_after_add` in the config turns
+        // it on for every `add`
+        open_after_add: bool,
+    },
+    RemoveJournal(String),
+    RemoveEmpty {
+        force: bool,
+    },
+    RemoveLogs(Vec<String>),
+    EditJournal(String),
+    EditLog {
+        uid: String,
+        note: Option<String>,
+        confirm: bool,
+    },
+    Last,
+    Stats {
+        histogram: bool,
+        granularity: Option<Granularity>,
+        // Only this journal, with a detailed report instead of the bands
+        journal: Option<String>,
+        // Totals per topic of `journal` instead of the report
+        by_topic: bool,
+    },
+    TemplateInit,
+    TemplateShow,
+    Review,
+    Recent(usize),
+    Weak(usize),
+    Plan(usize),
+    Archive {
+        uid: String,
+        // false for `unarchive`
+        archived: bool,
+    },
+    Goal {
+        journal: String,
+        goal: f32,
+    },
+    Color {
+        journal: String,
+        color: Option<String>,
+    },
+    Alias {
+        journal: String,
+        action: AliasAction,
+        alias: String,
+    },
+    Merge {
+        source: String,
+        dest: String,
+    },
+    // `force` merges logs about different subjects or topics too
+    MergeLogs {
+        keep: String,
+        other: String,
+        force: bool,
+    },
+    Export {
+        journal: Option<String>,
+        path: String,
+    },
+    Diff(String),
+    Open {
+        dir: bool,
+    },
+    OpenResource(String),
+    // `topic` renames topics instead of subjects
+    Rename {
+        old: String,
+        new: String,
+        topic: bool,
+    },
+    ConfigShow,
+    Purge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShowOptions {
+    pub metrics: bool,
+    pub compact: bool,
+    // How the journal percentages of `-m` are worked out, weighted when unset
+    pub mean: Option<Mean>,
+    pub legend: bool,
+    pub all: bool,
+    pub porcelain: bool,
+    pub color_scale: bool,
+    pub sparkline: bool,
+    pub include_archived: bool,
+    // Highest percentage first, from `sort` in the config or `get -s`.
+    // `sort` wins over it
+    pub sort_percentage: bool,
+    // Written to this file instead of stdout, without colors
+    pub out: Option<String>,
+    // Put on the clipboard instead of stdout, without colors
+    pub copy: bool,
+    pub reverse: bool,
+    pub sort: Option<SortKey>,
+    pub sort_journals: Option<JournalSort>,
+    // Turned into `filter.since` once today's date is known
+    pub since_days: Option<usize>,
+    pub format: TableFormat,
+    pub style: TableStyle,
+    pub filter: LogFilter,
+}
+impl Default for ShowOptions {
+    fn default() -> Self {
+        ShowOptions {
+            metrics: false,
+            compact: false,
+            mean: None,
+            legend: false,
+            all: false,
+            porcelain: false,
+            color_scale: false,
+            sparkline: false,
+            include_archived: false,
+            sort_percentage: false,
+            out: None,
+            copy: false,
+            reverse: false,
+            sort: None,
+            sort_journals: None,
+            since_days: None,
+            format: TableFormat::Compact,
+            style: TableStyle::Rounded,
+            filter: LogFilter::default(),
+        }
+    }
+}
+
+// What `get` looks for, guessed from the shape of the query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Tag {
+        tag: String,
+        include_archived: bool,
+    },
+    // `edit` opens the log in the editor once it was shown, `copy` puts it on
+    // the clipboard instead of printing it
+    Uid {
+        uid: String,
+        edit: bool,
+        copy: Option<CopyFormat>,
+    },
+    Text {
+        text: String,
+        sort: bool,
+        count_only: bool,
+        include_archived: bool,
+    },
+}
+
+// What `get <UID> --copy` puts on the clipboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CopyFormat {
+    // The table `get` prints
+    Table,
+    Json,
+}
+
+// Where `add <journal>` gets its logs from
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogSource {
+    Editor,
+    // CSV/TSV rows, see `import_logs`
+    File(String),
+    // A filled in note template, for scripts
+    Stdin,
+    // The editor, with the subject and topic of the log with this UID
+    Uid(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AliasAction {
+    Add,
+    Remove,
+}
+
+// Runs the subcommand in `args`, without the program name, against the data
+// file at `filepath`. Kept apart from `main` so tests can drive it
+pub fn run(args: Vec<String>, config: &Config, filepath: &Path) -> Result<(), ()> {
+    let command = parse_args(args).map_err(|err| {
+        if err.usage {
+            usage_stderr();
+        }
+        eprintln!("{}: {}", text("error").red(), err);
+    })?;
+    execute(command, config, filepath)
+}
+
+fn unknown_argument(arg: &str) -> StuError {
+    StuError::new(text_with("unknown_argument", &[&arg]))
+}
+
+fn missing_argument() -> StuError {
+    StuError::new(text("missing_argument"))
+}
+
+fn journal_name_not_provided() -> StuError {
+    StuError::new(text("journal_name_not_provided"))
+}
+
+// The name after `-j` or `--journal`, read the same way by every subcommand
+// scoped to a journal. A following flag is not taken as the name
+fn expect_journal_flag(args: &mut impl Iterator<Item = String>) -> Result<String, StuError> {
+    match args.next() {
+        Some(name) if !name.trim().is_empty() && !name.starts_with('-') => Ok(name),
+        _ => Err(journal_name_not_provided()),
+    }
+}
+
+// A positive count, `default` when none was given
+fn parse_count(arg: Option<String>, default: usize) -> Result<usize, StuError> {
+    match arg {
+        Some(x) => x
+            .parse::<usize>()
+            .ok()
+            .filter(|x| *x > 0)
+            .ok_or_else(|| StuError::new(text_with("invalid_count", &[&x]))),
+        None => Ok(default),
+    }
+}
+
+pub fn parse_args(args: Vec<String>) -> Result<Command, StuError> {
+    let mut args = args.into_iter().peekable();
+
+    // Arguments a subcommand doesn't use are refused unless `--lenient` comes
+    // before the subcommand, for scripts relying on them being ignored
+    let lenient = args.next_if(|arg| arg == "--lenient").is_some();
+
+    let subcommand = args
+        .next()
+        .ok_or_else(|| StuError::new(text("subcommand_needed")).with_usage())?;
+
+    let command = match subcommand.as_str() {
+        "-h" | "--help" => Command::Help,
+        "-V" | "--version" | "version" => Command::Version,
+        "show" => {
+            let mut options = ShowOptions::default();
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "-m" => options.metrics = true,
+                    "--compact" => options.compact = true,
+                    "--mean" => {
+                        let name = args.next().unwrap_or_default();
+                        options.mean =
+                            Some(Mean::from_name(&name).ok_or_else(|| {
+                                StuError::new(text_with("unknown_mean", &[&name]))
+                            })?);
+                    }
+                    "--subject" | "--topic" => {
+                        let value = args.next().ok_or_else(|| {
+                            StuError::new(text_with("filter_value_missing", &[&arg]))
+                        })?;
+                        if arg == "--subject" {
+                            options.filter.subject = Some(value);
+                        } else {
+                            options.filter.topic = Some(value);
+                        }
+                    }
+                    "--style" => {
+                        let name = args.next().unwrap_or_default();
+                        options.style = TableStyle::from_name(&name)
+                            .ok_or_else(|| StuError::new(text_with("unknown_style", &[&name])))?;
+                    }
+                    "--missing-dates" => options.filter.missing_date = true,
+                    "--since-days" => {
+                        let days = args.next().ok_or_else(|| {
+                            StuError::new(text_with("filter_value_missing", &[&arg]))
+                        })?;
+                        options.since_days = Some(parse_count(Some(days), 0)?);
+                    }
+                    "--reverse" => options.reverse = true,
+                    "--sort" => {
+                        let value = args.next().unwrap_or_default();
+                        options.sort = Some(SortKey::parse(&value).ok_or_else(|| {
+                            StuError::new(text_with(
+                                "unknown_sort_field",
+                                &[&value, &SortField::NAMES.join(", ")],
+                            ))
+                        })?);
+                    }
+                    "--sort-journals" => {
+                        let value = args.next().unwrap_or_default();
+                        options.sort_journals = Some(
+                            JournalSort::from_name(&value.to_lowercase()).ok_or_else(|| {
+                                StuError::new(text_with(
+                                    "unknown_journal_sort",
+                                    &[&value, &JournalSort::NAMES.join(", ")],
+                                ))
+                            })?,
+                        );
+                    }
+                    "--legend" => options.legend = true,
+                    "--all" => options.all = true,
+                    "--porcelain" => options.porcelain = true,
+                    "--color-scale" => options.color_scale = true,
+                    "--sparkline" => options.sparkline = true,
+                    "--include-archived" => options.include_archived = true,
+                    "--copy" => options.copy = true,
+                    "--out" => {
+                        options.out = Some(args.next().ok_or_else(|| {
+                            StuError::new(text_with("filter_value_missing", &[&arg]))
+                        })?);
+                    }
+                    "--format" => {
+                        let name = args.next().unwrap_or_default();
+                        options.format = TableFormat::from_name(&name)
+                            .ok_or_else(|| StuError::new(text_with("unknown_format", &[&name])))?;
+                    }
+                    _ => return Err(unknown_argument(&arg)),
+                }
+            }
+
+            if options.compact && !options.metrics {
+                return Err(StuError::new(text("compact_needs_metrics")));
+            }
+            if options.mean.is_some() && !options.metrics {
+                return Err(StuError::new(text("mean_needs_metrics")));
+            }
+
+            Command::Show(options)
+        }
+        "get" => Command::Get(parse_query(&mut args)?),
+        "add" => match args.next().as_deref() {
+            Some("-j" | "--journal") => {
+                let journal_name = expect_journal_flag(&mut args)?;
+                validate_journal_name(&journal_name)?;
+
+                let goal = match args.next().as_deref() {
+                    Some("--goal") => match args.next() {
+                        Some(x) => Some(parse_goal(&x)?),
+                        None => return Err(StuError::new(text("goal_not_provided"))),
+                    },
+                    Some(arg) => return Err(unknown_argument(arg)),
+                    None => None,
+                };
+
+                Command::AddJournal {
+                    name: journal_name,
+                    goal,
+                }
+            }
+            first => {
+                let mut journal = None;
+                let mut source = LogSource::Editor;
+                let mut date = None;
+                let mut open_after_add = false;
+                // The journal is optional and may come before or after the
+                // flags, it falls back to the default journal when left out
+                let mut next = first.map(str::to_string);
+                while let Some(arg) = next.take().or_else(|| args.next()) {
+                    let is_source =
+                        matches!(arg.as_str(), "--from-file" | "--stdin" | "--from-uid");
+                    if is_source && source != LogSource::Editor {
+                        return Err(StuError::new(text_with("log_source_twice", &[&arg])));
+                    }
+
+                    match arg.as_str() {
+                        "--from-file" => {
+                            source =
+                                LogSource::File(args.next().ok_or_else(|| {
+                                    StuError::new(text("import_file_not_provided"))
+                                })?)
+                        }
+                        "--stdin" => source = LogSource::Stdin,
+                        "--open-after-add" => open_after_add = true,
+                        "--from-uid" => match args.next() {
+                            Some(uid) if is_string_numeric(&uid) => source = LogSource::Uid(uid),
+                            Some(_) => return Err(StuError::new(text("invalid_uid"))),
+                            None => return Err(StuError::new(text("uid_not_provided"))),
+                        },
+                        "--date" => {
+                            let value = args.next().ok_or_else(|| {
+                                StuError::new(text_with(
+                                    "date_not_provided",
+                                    &[&date_format().pattern()],
+                                ))
+                            })?;
+                            date = Some(parse_date_arg(&value)?);
+                        }
+                        _ if !arg.starts_with('-') && journal.is_none() => journal = Some(arg),
+                        _ => return Err(unknown_argument(&arg)),
+                    }
+                }
+
+                Command::AddLogs {
+                    journal,
+                    source,
+                    date,
+                    open_after_add,
+                }
+            }
+        },
+        "remove" => match args.next().as_deref() {
+            Some("-j" | "--journal") => Command::RemoveJournal(expect_journal_flag(&mut args)?),
+            Some("--empty") => {
+                let force = match args.next().as_deref() {
+                    Some("--force") => true,
+                    Some(arg) => return Err(unknown_argument(arg)),
+                    None => false,
+                };
+
+                Command::RemoveEmpty { force }
+            }
+            Some(uid) => Command::RemoveLogs(
+                std::iter::once(uid.to_string())
+                    .chain(args.by_ref())
+                    .collect(),
+            ),
+            None => return Err(StuError::new(text("log_name_not_provided"))),
+        },
+        "edit" => match args.next().as_deref() {
+            Some("-j" | "--journal") => Command::EditJournal(expect_journal_flag(&mut args)?),
+            Some(uid) => {
+                if !is_string_numeric(uid) {
+                    return Err(StuError::new(text("invalid_uid")));
+                }
+
+                let mut note = None;
+                let mut confirm = false;
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--append-note" => {
+                            note = Some(
+                                args.next()
+                                    .ok_or_else(|| StuError::new(text("note_not_provided")))?,
+                            )
+                        }
+                        "--confirm" => confirm = true,
+                        _ => return Err(unknown_argument(&arg)),
+                    }
+                }
+
+                Command::EditLog {
+                    uid: uid.to_string(),
+                    note,
+                    confirm,
+                }
+            }
+            None => return Err(StuError::new(text("uid_not_provided"))),
+        },
+        "last" => Command::Last,
+        "stats" => {
+            let mut histogram = false;
+            let mut granularity = None;
+            let mut journal = None;
+            let mut by_topic = false;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--histogram" => histogram = true,
+                    "--by-topic" => by_topic = true,
+                    "-j" | "--journal" => journal = Some(expect_journal_flag(&mut args)?),
+                    "--by" => {
+                        let name = args.next().unwrap_or_default();
+                        granularity = Some(Granularity::from_name(&name).ok_or_else(|| {
+                            StuError::new(text_with("unknown_granularity", &[&name]))
+                        })?);
+                    }
+                    _ => return Err(unknown_argument(&arg)),
+                }
+            }
+
+            if !histogram && granularity.is_some() {
+                return Err(StuError::new(text("by_needs_histogram")));
+            }
+            if by_topic && (histogram || journal.is_none()) {
+                return Err(StuError::new(text("by_topic_needs_journal")));
+            }
+
+            Command::Stats {
+                histogram,
+                granularity,
+                journal,
+                by_topic,
+            }
+        }
+        "template" => match args.next().as_deref() {
+            Some("--init") => Command::TemplateInit,
+            Some("--show") => Command::TemplateShow,
+            Some(arg) => return Err(unknown_argument(arg)),
+            None => return Err(missing_argument()),
+        },
+        "review" => Command::Review,
+        "recent" => Command::Recent(parse_count(args.next(), DEFAULT_RECENT_COUNT)?),
+        "weak" => Command::Weak(parse_count(args.next(), DEFAULT_WEAK_COUNT)?),
+        "plan" => Command::Plan(parse_count(args.next(), DEFAULT_PLAN_COUNT)?),
+        "archive" | "unarchive" => Command::Archive {
+            archived: subcommand == "archive",
+            uid: args
+                .next()
+                .ok_or_else(|| StuError::new(text("uid_not_provided")))?,
+        },
+        "goal" => {
+            let journal = args.next().ok_or_else(journal_name_not_provided)?;
+            let goal = match args.next() {
+                Some(x) => parse_goal(&x)?,
+                None => return Err(StuError::new(text("goal_not_provided"))),
+            };
+
+            Command::Goal { journal, goal }
+        }
+        "color" => {
+            let journal = args.next().ok_or_else(journal_name_not_provided)?;
+            let color = match args.next() {
+                Some(x) if x == "none" => None,
+                Some(x) if parse_color(&x).is_some() => Some(x.trim().to_lowercase()),
+                Some(x) => {
+                    return Err(StuError::new(text_with(
+                        "unknown_color",
+                        &[&x, &COLOR_NAMES.join(", ")],
+                    )));
+                }
+                None => return Err(StuError::new(text("color_not_provided"))),
+            };
+
+            Command::Color { journal, color }
+        }
+        "alias" => {
+            let journal = args.next().ok_or_else(journal_name_not_provided)?;
+            let action = args.next().unwrap_or_default();
+            let alias = args
+                .next()
+                .ok_or_else(|| StuError::new(text("alias_not_provided")))?;
+            let action = match action.as_str() {
+                "add" => {
+                    validate_journal_name(&alias)?;
+                    AliasAction::Add
+                }
+                "remove" => AliasAction::Remove,
+                "" => return Err(missing_argument()),
+                action => return Err(unknown_argument(action)),
+            };
+
+            Command::Alias {
+                journal,
+                action,
+                alias,
+            }
+        }
+        "merge" => {
+            let source = args.next().ok_or_else(journal_name_not_provided)?;
+            let dest = args.next().ok_or_else(journal_name_not_provided)?;
+
+            Command::Merge { source, dest }
+        }
+        "rename-subject" => {
+            let mut names: Vec<String> = Vec::new();
+            let mut topic = false;
+            for arg in args.by_ref() {
+                match arg.as_str() {
+                    "--topic" => topic = true,
+                    _ if names.len() < 2 => names.push(arg),
+                    _ => return Err(unknown_argument(&arg)),
+                }
+            }
+
+            let [old, new]: [String; 2] = names
+                .try_into()
+                .map_err(|_| StuError::new(text("rename_needs_two")))?;
+            if new.trim().is_empty() {
+                return Err(StuError::new(text("rename_empty")));
+            }
+
+            Command::Rename {
+                old,
+                new: new.trim().to_string(),
+                topic,
+            }
+        }
+        "merge-logs" => {
+            let mut uids: Vec<String> = Vec::new();
+            let mut force = false;
+            for arg in args.by_ref() {
+                match arg.as_str() {
+                    "--force" => force = true,
+                    uid if is_string_numeric(uid) && uids.len() < 2 => uids.push(arg),
+                    _ => return Err(unknown_argument(&arg)),
+                }
+            }
+
+            let [keep, other]: [String; 2] = uids
+                .try_into()
+                .map_err(|_| StuError::new(text("merge_logs_needs_two")))?;
+            if keep == other {
+                return Err(StuError::new(text("merge_same_log")));
+            }
+
+            Command::MergeLogs { keep, other, force }
+        }
+        "export" => {
+            let mut journal: Option<String> = None;
+            let mut path: Option<String> = None;
+            let path_not_provided = || StuError::new(text("export_path_not_provided"));
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "-j" | "--journal" => journal = Some(expect_journal_flag(&mut args)?),
+                    "--json" => path = Some(args.next().ok_or_else(path_not_provided)?),
+                    _ => return Err(unknown_argument(&arg)),
+                }
+            }
+
+            Command::Export {
+                journal,
+                path: path.ok_or_else(path_not_provided)?,
+            }
+        }
+        "purge" => Command::Purge,
+        "diff" => Command::Diff(
+            args.next()
+                .ok_or_else(|| StuError::new(text("diff_file_not_provided")))?,
+        ),
+        "open" => match args.next().as_deref() {
+            Some("--dir") => Command::Open { dir: true },
+            Some(uid) if is_string_numeric(uid) => match args.next().as_deref() {
+                Some("--resource") => Command::OpenResource(uid.to_string()),
+                Some(arg) => return Err(unknown_argument(arg)),
+                None => return Err(missing_argument()),
+            },
+            Some(arg) => return Err(unknown_argument(arg)),
+            None => Command::Open { dir: false },
+        },
+        "config" => match args.next().as_deref() {
+            Some("--show") => Command::ConfigShow,
+            Some(arg) => return Err(unknown_argument(arg)),
+            None => return Err(missing_argument()),
+        },
+        _ => {
+            return Err(StuError::new(text_with(
+                "unexpected_subcommand",
+                &[&subcommand],
+            )));
+        }
+    };
+
+    if let Some(arg) = args.next().filter(|_| !lenient) {
+        return Err(unknown_argument(&arg));
+    }
+
+    Ok(command)
+}
+
+// `get <query>`, guessing what kind of query it is from its shape
+fn parse_query(args: &mut impl Iterator<Item = String>) -> Result<Query, StuError> {
+    // Only spaces would match nothing, which reads like a failed search
+    let mut next_query = || {
+        let query = args
+            .next()
+            .ok_or_else(|| StuError::new(text("query_not_provided")))?;
+        if query.trim().is_empty() {
+            return Err(StuError::new(text("query_empty")));
+        }
+        Ok(query.trim().to_string())
+    };
+    let value = next_query()?;
+
+    if value == "--tag" {
+        let tag = args
+            .next()
+            .ok_or_else(|| StuError::new(text("tag_not_provided")))?;
+        let include_archived = match args.next().as_deref() {
+            Some("--include-archived") => true,
+            Some(arg) => return Err(unknown_argument(arg)),
+            None => false,
+        };
+        return Ok(Query::Tag {
+            tag: tag.to_lowercase(),
+            include_archived,
+        });
+    }
+
+    if is_string_numeric(&value) {
+        let (edit, copy) = match args.next().as_deref() {
+            Some("--edit") => (true, None),
+            Some("--copy") => (false, Some(CopyFormat::Table)),
+            Some("--copy-json") => (false, Some(CopyFormat::Json)),
+            Some(arg) => return Err(unknown_argument(arg)),
+            None => (false, None),
+        };
+        return Ok(Query::Uid {
+            uid: value,
+            edit,
+            copy,
+        });
+    }
+
+    let (query, sort) = if value == "-s" {
+        (next_query()?, true)
+    } else {
+        (value, false)
+    };
+
+    if !is_string_alphanumeric(&query) {
+        return Err(StuError::new(text("unknown_query_type")));
+    }
+
+    let mut count_only = false;
+    let mut include_archived = false;
+    for arg in args.by_ref() {
+        match arg.as_str() {
+            "--count-only" => count_only = true,
+            "--include-archived" => include_archived = true,
+            // A text query can match many logs, there is no single one to edit
+            "--edit" => return Err(StuError::new(text("edit_needs_uid"))),
+            _ => return Err(unknown_argument(&arg)),
+        }
+    }
+
+    Ok(Query::Text {
+        text: query.to_lowercase(),
+        sort,
+        count_only,
+        include_archived,
+    })
+}
+
+pub fn execute(command: Command, config: &Config, filepath: &Path) -> Result<(), ()> {
+    match command {
+        Command::Help => usage_stdout(),
+        Command::Version => println!("stu {VERSION}"),
+        Command::Show(options) if options.legend => stu::show_legend(&config.subject_colors),
+        Command::Show(options) => show(
+            &mut JournalStore::load(filepath)?,
+            ShowOptions {
+                sort_percentage: config.sort,
+                ..options
+            },
+        )?,
+        Command::Get(query) => get(query, config, filepath)?,
+        Command::AddJournal { name, goal } => {
+            add_journal(&mut JournalStore::load(filepath)?, &name, goal)?
+        }
+        Command::AddLogs {
+            journal,
+            source,
+            date,
+            open_after_add,
+        } => {
+            // The name on the command line, then `STU_DEFAULT_JOURNAL`, then
+            // `default_journal` from the config
+            let journal = journal
+                .or_else(|| config.default_journal.clone())
+                .ok_or_else(|| {
+                    eprintln!(
+                        "{}: {}",
+                        text("error").red(),
+                        text("journal_name_not_provided_hint")
+                    );
+                })?;
+            let date = date.unwrap_or_else(get_date);
+            add_logs(
+                &mut JournalStore::load(filepath)?,
+                &journal,
+                source,
+                &date,
+                open_after_add || config.open_after_add,
+            )?
+        }
+        Command::RemoveJournal(name) => remove_journal(
+            &mut JournalStore::load_without_percentages(filepath)?,
+            &name,
+        )?,
+        Command::RemoveEmpty { force } => remove_empty(
+            &mut JournalStore::load_without_percentages(filepath)?,
+            force,
+        )?,
+        Command::RemoveLogs(uids) => remove_logs(
+            &mut JournalStore::load_without_percentages(filepath)?,
+            &uids,
+        )?,
+        Command::EditJournal(name) => edit_journal(&mut JournalStore::load(filepath)?, &name)?,
+        Command::EditLog { uid, note, confirm } => {
+            edit_log(&mut JournalStore::load(filepath)?, &uid, note, confirm)?
+        }
+        Command::Last => last(&mut JournalStore::load(filepath)?)?,
+        Command::Stats {
+            histogram,
+            granularity,
+            journal,
+            by_topic,
+        } => stats(
+            &load_view(filepath)?,
+            histogram,
+            granularity,
+            journal.as_deref(),
+            by_topic,
+        )?,
+        Command::TemplateInit => {
+            let data_dir = utils::data_dir_of(filepath);
+            stu::init_template(data_dir)?;
+            println!(
+                "{}",
+                messages::created_template(&template_path(data_dir)).green()
+            );
+        }
+        Command::TemplateShow => stu::preview_template(utils::data_dir_of(filepath)),
+        Command::Review => review(&load_view(filepath)?, config)?,
+        Command::Recent(count) => stu::show_recent(&load_view(filepath)?.journals, count),
+        Command::Weak(count) => stu::show_weak(&load_view(filepath)?.journals, count),
+        Command::Plan(count) => plan(&load_view(filepath)?, count)?,
+        Command::Archive { uid, archived } => archive(
+            &mut JournalStore::load_without_percentages(filepath)?,
+            &uid,
+            archived,
+        )?,
+        Command::Goal { journal, goal } => set_goal(
+            &mut JournalStore::load_without_percentages(filepath)?,
+            &journal,
+            goal,
+        )?,
+        Command::Color { journal, color } => set_color(
+            &mut JournalStore::load_without_percentages(filepath)?,
+            &journal,
+            color,
+        )?,
+        Command::Alias {
+            journal,
+            action,
+            alias,
+        } => edit_alias(
+            &mut JournalStore::load_without_percentages(filepath)?,
+            &journal,
+            action,
+            &alias,
+        )?,
+        Command::Merge { source, dest } => {
+            merge(&mut JournalStore::load(filepath)?, &source, &dest)?
+        }
+        Command::MergeLogs { keep, other, force } => {
+            merge_logs(&mut JournalStore::load(filepath)?, &keep, &other, force)?
+        }
+        Command::Export { journal, path } => {
+            export(JournalStore::load(filepath)?, journal.as_deref(), &path)?
+        }
+        Command::Diff(other_filepath) => stu::show_diff(
+            &JournalStore::load(filepath)?.journals,
+            &stu::read_journals(&other_filepath)?,
+        ),
+        Command::Open { dir } => open(filepath, dir)?,
+        Command::Rename { old, new, topic } => rename(
+            &mut JournalStore::load_without_percentages(filepath)?,
+            &old,
+            &new,
+            topic,
+        )?,
+        Command::OpenResource(uid) => open_resource(&mut JournalStore::load(filepath)?, &uid)?,
+        Command::ConfigShow => config.show(),
+        Command::Purge => purge(&mut JournalStore::load(filepath)?)?,
+    }
+
+    Ok(())
+}
+
+// The journal called `name`, refusing names shared by several journals so a
+// command never silently acts on the first of them
+fn find_journal(store: &JournalStore, name: &str) -> Result<Option<usize>, ()> {
+    let matches: Vec<usize> = store
+        .journals
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| x.is_named(name))
+        .map(|(index, _)| index)
+        .collect();
+
+    if matches.len() > 1 {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("ambiguous_journal", &[&name, &matches.len()])
+        );
+        return Err(());
+    }
+    Ok(matches.first().copied())
+}
+
+fn journal_index(store: &JournalStore, name: &str) -> Result<usize, ()> {
+    find_journal(store, name)?.ok_or_else(|| {
+        eprintln!("{}", text_with("journal_not_found", &[&name]).red());
+    })
+}
+
+fn show(store: &mut JournalStore, mut options: ShowOptions) -> Result<(), ()> {
+    let journals = &mut store.journals;
+
+    if journals.is_empty() {
+        eprintln!("{}", text("no_journals").red());
+
+        return Err(());
+    }
+
+    stu::hide_archived(journals, &options);
+
+    // The last `days` days, today included
+    if let Some(days) = options.since_days {
+        let today = today().ok_or_else(|| {
+            eprintln!("{}: {}", text("error").red(), text("unknown_today"));
+        })?;
+        options.filter.since = Some(today - days as i64 + 1);
+    }
+
+    if options.copy && options.out.is_some() {
+        eprintln!("{}: {}", text("error").red(), text("copy_with_out"));
+        return Err(());
+    }
+
+    let filter = &options.filter;
+    let mut count = None;
+    if !filter.is_empty() {
+        filter.apply(journals);
+
+        let matched: usize = journals.iter().map(|x| x.logs.len()).sum();
+        if matched == 0 && !options.porcelain {
+            if filter.missing_date {
+                eprintln!("{}", text("no_missing_dates").green());
+                return Ok(());
+            }
+            eprintln!("{}", text("no_matching_logs").red());
+            return Err(());
+        }
+        count = Some(matched);
+    }
+
+    // Opened once there is something to show, so `--out` never truncates a
+    // file for nothing. Colors would end up as escape codes in the file or
+    // the clipboard
+    let mut copied: Vec<u8> = Vec::new();
+    let mut out: Box<dyn Write + '_> = match &options.out {
+        Some(path) => {
+            colored::control::set_override(false);
+            Box::new(File::create(path).map_err(|err| {
+                eprintln!(
+                    "{}: {}",
+                    text("error").red(),
+                    text_with("create_file_failed", &[&err])
+                );
+            })?)
+        }
+        None if options.copy => {
+            colored::control::set_override(false);
+            Box::new(&mut copied)
+        }
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    if let Some(count) = count.filter(|_| !options.metrics && !options.porcelain) {
+        let _ = writeln!(out, "{}", messages::results(count));
+    }
+
+    // After filtering, so the percentages are the ones shown
+    if let Some(by) = options.sort_journals {
+        stu::sort_journals(journals, by);
+    }
+
+    let written = if options.porcelain {
+        stu::show_porcelain(&mut out, journals, &options)
+    } else if options.metrics && options.compact {
+        stu::show_metrics_compact(
+            &mut out,
+            journals,
+            options.style,
+            options.mean.unwrap_or_default(),
+        )
+    } else if options.metrics {
+        stu::show_metrics(
+            &mut out,
+            journals,
+            options.style,
+            options.mean.unwrap_or_default(),
+        )
+    } else if options.all || filter.missing_date {
+        stu::show_all(&mut out, journals, &options)
+    } else if !filter.is_empty() {
+        let mut query_journal = Journal::new("Query");
+        for journal in journals.drain(..) {
+            query_journal.logs.extend(journal.logs);
+        }
+        stu::show_journals(&mut out, &mut [query_journal], &options)
+    } else {
+        stu::show_journals(&mut out, journals, &options)
+    };
+
+    if let Some(path) = &options.out {
+        written.and_then(|()| out.flush()).map_err(|err| {
+            eprintln!(
+                "{}: {}",
+                text("error").red(),
+                text_with("write_output_failed", &[&path, &err])
+            );
+        })?;
+        println!("{}", messages::wrote_output(path).green());
+    }
+    if options.copy {
+        drop(out);
+        utils::copy_to_clipboard(&String::from_utf8_lossy(&copied))?;
+        println!("{}", text("copied_tables").green());
+    }
+
+    Ok(())
+}
+
+fn get(query: Query, config: &Config, filepath: &Path) -> Result<(), ()> {
+    let mut options = ShowOptions {
+        sort_percentage: config.sort,
+        ..ShowOptions::default()
+    };
+    if let Query::Tag {
+        include_archived: true,
+        ..
+    }
+    | Query::Text {
+        include_archived: true,
+        ..
+    } = query
+    {
+        options.include_archived = true;
+    }
+
+    match query {
+        Query::Tag { tag, .. } => stu::query_tag(&tag, filepath, &options),
+        Query::Uid {
+            uid,
+            copy: Some(format),
+            ..
+        } => copy_log(&JournalStore::load(filepath)?, &uid, format),
+        Query::Uid {
+            uid, edit: false, ..
+        } => stu::query_uid(&uid, filepath),
+        Query::Uid {
+            uid, edit: true, ..
+        } => {
+            stu::query_uid(&uid, filepath)?;
+            edit_log(&mut JournalStore::load(filepath)?, &uid, None, false)
+        }
+        Query::Text {
+            text,
+            count_only: true,
+            ..
+        } => stu::count_for(&text, filepath, &options),
+        Query::Text { text, sort, .. } => {
+            options.sort_percentage |= sort;
+            stu::query_for(&text, filepath, &options)
+        }
+    }
+}
+
+fn copy_log(store: &JournalStore, uid: &str, format: CopyFormat) -> Result<(), ()> {
+    let uid = stu::resolve_uid_prefix(&store.journals, uid)?;
+    let log = store
+        .journals
+        .iter()
+        .flat_map(|journal| journal.logs.iter())
+        .find(|log| log.uid == uid)
+        .ok_or_else(|| {
+            eprintln!("{}", text_with("uid_not_found", &[&uid]).red());
+        })?;
+
+    let content = match format {
+        CopyFormat::Table => {
+            // Escape codes would be pasted along with the text
+            colored::control::set_override(false);
+            stu::render_log(log)
+        }
+        CopyFormat::Json => serde_json::to_string_pretty(log).unwrap(),
+    };
+    utils::copy_to_clipboard(&content)?;
+    println!("{}", text_with("copied_log", &[&uid]).green());
+
+    Ok(())
+}
+
+fn add_journal(store: &mut JournalStore, name: &str, goal: Option<f32>) -> Result<(), ()> {
+    // Two journals with one name would make every command refuse it, see
+    // `duplicate_journal_names`
+    if store.journals.iter().any(|journal| journal.name == name) {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("journal_exists", &[&name])
+        );
+        return Err(());
+    }
+    if stu::name_taken(&store.journals, name) {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("name_taken", &[&name])
+        );
+        return Err(());
+    }
+
+    let mut new_journal = Journal::new(name);
+    new_journal.goal_percentage = goal;
+    new_journal.add_log(stu::make_log(name, &get_date(), None, store.dir())?);
+    store.journals.push(new_journal);
+
+    store.save()?;
+    stu::clear_draft(store.dir());
+    println!("{}", messages::created_journal().green());
+    Ok(())
+}
+
+fn add_logs(
+    store: &mut JournalStore,
+    query: &str,
+    source: LogSource,
+    date: &str,
+    open_after_add: bool,
+) -> Result<(), ()> {
+    let index = match find_journal(store, query)? {
+        Some(index) => index,
+        None => {
+            stu::list_journals(&store.journals);
+            println!(
+                "{text}[y/n]",
+                text = text_with("journal_not_found_create", &[&query]).red()
+            );
+            return Ok(());
+        }
+    };
+    let journal_name = store.journals[index].name.clone();
+
+    let new_logs = match &source {
+        LogSource::Editor => vec![stu::make_log(&journal_name, date, None, store.dir())?],
+        LogSource::Uid(uid) => {
+            let uid = &stu::resolve_uid_prefix(&store.journals, uid)?;
+            let seed = store
+                .journals
+                .iter()
+                .flat_map(|journal| journal.logs.iter())
+                .find(|log| &log.uid == uid)
+                .ok_or_else(|| {
+                    eprintln!("{}", text_with("log_not_found", &[&uid]).red());
+                })?;
+            vec![stu::make_log(&journal_name, date, Some(seed), store.dir())?]
+        }
+        LogSource::File(path) => stu::import_logs(path, &store.journals, date, store.dir())?,
+        LogSource::Stdin => vec![stu::log_from_stdin(date, store.dir())?],
+    };
+    let count = new_logs.len();
+    store.journals[index].logs.extend(new_logs);
+    let first_new = store.journals[index].logs.len() - count;
+
+    store.save()?;
+    match source {
+        LogSource::File(_) => {
+            println!("{}", messages::imported_logs(count, &journal_name).green())
+        }
+        LogSource::Editor | LogSource::Uid(_) => {
+            stu::clear_draft(store.dir());
+            println!("{}", messages::added_log(&journal_name).green());
+        }
+        LogSource::Stdin => println!("{}", messages::added_log(&journal_name).green()),
+    }
+    if open_after_add {
+        for log in store.journals[index].logs[first_new..].iter() {
+            stu::show_log(log);
+        }
+    }
+    Ok(())
+}
+
+fn remove_journal(store: &mut JournalStore, name: &str) -> Result<(), ()> {
+    let removed = store.journals.remove(journal_index(store, name)?);
+
+    store.save()?;
+    println!("{}", messages::removed_journal(&removed.name).green());
+    Ok(())
+}
+
+fn remove_empty(store: &mut JournalStore, force: bool) -> Result<(), ()> {
+    let empty: Vec<&str> = store
+        .journals
+        .iter()
+        .filter(|journal| journal.logs.is_empty())
+        .map(|journal| journal.name.as_str())
+        .collect();
+    if empty.is_empty() {
+        println!("{}", text("no_empty_journals").green());
+        return Ok(());
+    }
+
+    let question = text_with("remove_empty_confirm", &[&empty.join(", ")]);
+    if !force && !confirm(&question) {
+        return Err(());
+    }
+
+    let count = empty.len();
+    store.journals.retain(|journal| !journal.logs.is_empty());
+
+    store.save()?;
+    println!("{}", messages::pruned_journals(count).green());
+    Ok(())
+}
+
+// Deletes every journal once confirmed twice and with `DELETE` typed out, the
+// old file is kept as a backup named after the time of the purge
+fn purge(store: &mut JournalStore) -> Result<(), ()> {
+    let confirmed = confirm(&text_with("purge_confirm", &[&store.path.display()]))
+        && confirm(text("purge_confirm_again"))
+        && prompt(text("purge_type_delete")).is_some_and(|answer| answer.trim() == "DELETE");
+    if !confirmed {
+        eprintln!("{}", text("purge_cancelled").red());
+        return Err(());
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs());
+    let backup = stu::backup_data(&store.path, &format!("purge-{now}"))?;
+    store.journals.clear();
+
+    store.save()?;
+    println!(
+        "{}",
+        messages::purged(&backup.display().to_string()).green()
+    );
+    Ok(())
+}
+
+// UIDs that match no log are reported after the removed ones, the command
+// only fails when none of them matched
+fn remove_logs(store: &mut JournalStore, uids: &[String]) -> Result<(), ()> {
+    let mut removed: Vec<String> = Vec::new();
+    // (prefix, UIDs starting with it) of the UIDs that didn't resolve
+    let mut missed: Vec<(&str, Vec<String>)> = Vec::new();
+    for prefix in uids.iter() {
+        let uid = match stu::match_uid_prefix(&store.journals, prefix) {
+            Ok(uid) => uid,
+            Err(matches) => {
+                missed.push((prefix, matches.iter().map(|x| x.to_string()).collect()));
+                continue;
+            }
+        };
+        // The same UID given twice is only found the first time
+        match stu::find_log_mut(&mut store.journals, &uid) {
+            Some((journal_index, log_index)) => {
+                store.journals[journal_index].logs.remove(log_index);
+                removed.push(uid);
+            }
+            None => missed.push((prefix, Vec::new())),
+        }
+    }
+
+    if !removed.is_empty() {
+        store.save()?;
+    }
+    for uid in removed.iter() {
+        println!("{}", messages::removed_log(uid).green());
+    }
+    for (prefix, matches) in missed.iter() {
+        let matches: Vec<&str> = matches.iter().map(|x| x.as_str()).collect();
+        stu::uid_prefix_missed(prefix, &matches);
+    }
+
+    if removed.is_empty() {
+        eprintln!("{}: {}", text("error").red(), text("no_logs_removed"));
+        return Err(());
+    }
+    if !missed.is_empty() {
+        println!(
+            "{}",
+            text_with("removed_logs_partly", &[&removed.len(), &uids.len()]).yellow()
+        );
+    }
+    Ok(())
+}
+
+fn edit_journal(store: &mut JournalStore, name: &str) -> Result<(), ()> {
+    let index = journal_index(store, name)?;
+    let journal = &mut store.journals[index];
+
+    let new_logs = stu::edit_journal(journal)?;
+    for new_log in new_logs.iter() {
+        for log in journal.logs.iter_mut() {
+            if log.uid == new_log.uid {
+                *log = new_log.clone();
+            }
+        }
+    }
+
+    store.save()?;
+    println!("{}", messages::edited_journal(new_logs.len(), name).green());
+    Ok(())
+}
+
+fn edit_log(
+    store: &mut JournalStore,
+    uid: &str,
+    note: Option<String>,
+    confirm: bool,
+) -> Result<(), ()> {
+    let uid = &stu::resolve_uid_prefix(&store.journals, uid)?;
+    let (journal_index, log_index) =
+        stu::find_log_mut(&mut store.journals, uid).ok_or_else(|| {
+            eprintln!("{}", text_with("log_not_found", &[&uid]).red());
+        })?;
+
+    let data_dir = store.dir().to_path_buf();
+    let journal = &mut store.journals[journal_index];
+    let log = &mut journal.logs[log_index];
+    match &note {
+        Some(note) => log.append_note(&get_date(), note),
+        None => {
+            let edited = stu::edit_log(log.clone(), &journal.name, &data_dir)?;
+            stu::show_log_changes(log, &edited);
+            if confirm && !utils::confirm(text("save_changes_confirm")) {
+                return Err(());
+            }
+            *log = edited;
+        }
+    }
+
+    store.save()?;
+    println!("{}", messages::edited_log(uid).green());
+    Ok(())
+}
+
+fn last(store: &mut JournalStore) -> Result<(), ()> {
+    let (journal_index, log_index) = stu::find_last_log(&store.journals).ok_or_else(|| {
+        eprintln!("{}", text("no_logs").red());
+    })?;
+
+    let data_dir = store.dir().to_path_buf();
+    let journal = &mut store.journals[journal_index];
+    let log = &mut journal.logs[log_index];
+    *log = stu::edit_log(log.clone(), &journal.name, &data_dir)?;
+    let uid = log.uid.clone();
+
+    store.save()?;
+    println!("{}", messages::edited_log(&uid).green());
+    Ok(())
+}
+
+fn stats(
+    store: &JournalStore,
+    histogram: bool,
+    granularity: Option<Granularity>,
+    journal: Option<&str>,
+    by_topic: bool,
+) -> Result<(), ()> {
+    let journals = match journal {
+        Some(name) => std::slice::from_ref(&store.journals[journal_index(store, name)?]),
+        None => &store.journals[..],
+    };
+
+    if !histogram {
+        match journal {
+            Some(_) if by_topic => stu::show_topic_stats(&stu::stats_by_topic(&journals[0])),
+            Some(_) => stu::show_journal_report(&journals[0]),
+            None => stu::show_bands(&stu::percentage_bands(journals)),
+        }
+        return Ok(());
+    }
+
+    let buckets = stu::histogram(journals, granularity.unwrap_or(Granularity::Week));
+    if buckets.is_empty() {
+        eprintln!("{}", text("no_dated_logs").red());
+        return Err(());
+    }
+
+    stu::show_histogram(&buckets);
+    Ok(())
+}
+
+fn review(store: &JournalStore, config: &Config) -> Result<(), ()> {
+    let today = today().ok_or_else(|| {
+        eprintln!("{}: {}", text("error").red(), text("unknown_today"));
+    })?;
+
+    let mut review_journal = Journal::new(text("review_journal"));
+    for log in stu::due_logs(&store.journals, today) {
+        review_journal.add_log(log);
+    }
+
+    if review_journal.logs.is_empty() {
+        println!("{}", text("nothing_to_review").green());
+        return Ok(());
+    }
+
+    let options = ShowOptions {
+        sort_percentage: config.sort,
+        ..ShowOptions::default()
+    };
+    let _ = stu::show_journals(
+        &mut std::io::stdout().lock(),
+        &mut [review_journal],
+        &options,
+    );
+    Ok(())
+}
+
+// The journals as the read only views show them, without archived logs
+fn load_view(filepath: &Path) -> Result<JournalStore, ()> {
+    let mut store = JournalStore::load(filepath)?;
+    stu::hide_archived(&mut store.journals, &ShowOptions::default());
+    Ok(store)
+}
+
+fn archive(store: &mut JournalStore, uid: &str, archived: bool) -> Result<(), ()> {
+    let uid = stu::resolve_uid_prefix(&store.journals, uid)?;
+    let (journal_index, log_index) =
+        stu::find_log_mut(&mut store.journals, &uid).ok_or_else(|| {
+            eprintln!("{}", text_with("uid_not_found", &[&uid]).red());
+        })?;
+    let log = &mut store.journals[journal_index].logs[log_index];
+
+    if log.archived == archived {
+        let key = if archived {
+            "already_archived"
+        } else {
+            "not_archived"
+        };
+        println!("{}", text_with(key, &[&uid]).dimmed());
+        return Ok(());
+    }
+
+    log.archived = archived;
+    store.save()?;
+    let key = if archived {
+        "archived_log"
+    } else {
+        "unarchived_log"
+    };
+    println!("{}", text_with(key, &[&uid]).green());
+    Ok(())
+}
+
+fn plan(store: &JournalStore, count: usize) -> Result<(), ()> {
+    let today = today().ok_or_else(|| {
+        eprintln!("{}: {}", text("error").red(), text("unknown_today"));
+    })?;
+
+    stu::show_plan(&stu::study_plan(&store.journals, today, count));
+    Ok(())
+}
+
+fn set_goal(store: &mut JournalStore, name: &str, goal: f32) -> Result<(), ()> {
+    let index = journal_index(store, name)?;
+    store.journals[index].goal_percentage = Some(goal);
+
+    store.save()?;
+    println!("{}", messages::goal_set(goal, name).green());
+    Ok(())
+}
+
+fn set_color(store: &mut JournalStore, name: &str, color: Option<String>) -> Result<(), ()> {
+    let index = journal_index(store, name)?;
+    store.journals[index].color = color.clone();
+
+    store.save()?;
+    match color {
+        Some(color) => println!("{}", messages::color_set(&color, name).green()),
+        None => println!("{}", messages::color_cleared(name).green()),
+    }
+    Ok(())
+}
+
+fn edit_alias(
+    store: &mut JournalStore,
+    name: &str,
+    action: AliasAction,
+    alias: &str,
+) -> Result<(), ()> {
+    let index = journal_index(store, name)?;
+
+    match action {
+        AliasAction::Add => {
+            if stu::name_taken(&store.journals, alias) {
+                eprintln!(
+                    "{}: {}",
+                    text("error").red(),
+                    text_with("name_taken", &[&alias])
+                );
+                return Err(());
+            }
+            store.journals[index].aliases.push(alias.to_string());
+        }
+        AliasAction::Remove => {
+            let journal = &mut store.journals[index];
+            let i = journal
+                .aliases
+                .iter()
+                .position(|x| x == alias)
+                .ok_or_else(|| {
+                    eprintln!(
+                        "{}",
+                        text_with("alias_not_found", &[&journal.name, &alias]).red()
+                    );
+                })?;
+            journal.aliases.remove(i);
+        }
+    }
+
+    store.save()?;
+    let journal_name = &store.journals[index].name;
+    match action {
+        AliasAction::Add => println!("{}", messages::alias_added(alias, journal_name).green()),
+        AliasAction::Remove => {
+            println!("{}", messages::alias_removed(alias, journal_name).green())
+        }
+    }
+    Ok(())
+}
+
+fn merge(store: &mut JournalStore, source: &str, dest: &str) -> Result<(), ()> {
+    let (source, dest) = (journal_index(store, source)?, journal_index(store, dest)?);
+    if source == dest {
+        eprintln!("{}: {}", text("error").red(), text("merge_same_journal"));
+        return Err(());
+    }
+
+    let source_name = store.journals[source].name.clone();
+    let dest_name = store.journals[dest].name.clone();
+    let (merged, skipped) = stu::merge_journals(&mut store.journals, source, dest);
+
+    store.save()?;
+    println!(
+        "{}",
+        messages::merged_journals(merged, &source_name, &dest_name).green()
+    );
+    if skipped > 0 {
+        println!(
+            "{}",
+            text_with("merge_skipped", &[&skipped, &source_name, &dest_name]).yellow()
+        );
+    }
+    Ok(())
+}
+
+fn rename(store: &mut JournalStore, old: &str, new: &str, topic: bool) -> Result<(), ()> {
+    let count = stu::rename_field(&mut store.journals, old, new, topic);
+    if count == 0 {
+        eprintln!("{}", text("no_matching_logs").red());
+        return Err(());
+    }
+
+    store.save()?;
+    println!("{}", messages::renamed(count, old, new).green());
+    Ok(())
+}
+
+fn merge_logs(store: &mut JournalStore, keep: &str, other: &str, force: bool) -> Result<(), ()> {
+    let keep = &stu::resolve_uid_prefix(&store.journals, keep)?;
+    let other = &stu::resolve_uid_prefix(&store.journals, other)?;
+    if keep == other {
+        eprintln!("{}: {}", text("error").red(), text("merge_same_log"));
+        return Err(());
+    }
+
+    let find = |journals: &mut [Journal], uid: &str| {
+        stu::find_log_mut(journals, uid).ok_or_else(|| {
+            eprintln!("{}", text_with("log_not_found", &[&uid]).red());
+        })
+    };
+    let (keep_journal, keep_index) = find(&mut store.journals, keep)?;
+    let (other_journal, other_index) = find(&mut store.journals, other)?;
+
+    let other_log = store.journals[other_journal].logs[other_index].clone();
+    let kept_log = &store.journals[keep_journal].logs[keep_index];
+    let same_session = kept_log.subject.to_lowercase() == other_log.subject.to_lowercase()
+        && kept_log.topic_label().to_lowercase() == other_log.topic_label().to_lowercase();
+    if !same_session && !force {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("merge_logs_different", &[&keep, &other])
+        );
+        return Err(());
+    }
+
+    stu::merge_logs(
+        &mut store.journals[keep_journal].logs[keep_index],
+        &other_log,
+    );
+    store.journals[other_journal].logs.remove(other_index);
+
+    store.save()?;
+    println!("{}", messages::merged_logs(other, keep).green());
+    Ok(())
+}
+
+fn export(store: JournalStore, journal_name: Option<&str>, out_path: &str) -> Result<(), ()> {
+    let mut journals = store.journals;
+
+    // A single journal is still written as an array so it reads like a data file
+    if let Some(journal_name) = journal_name {
+        journals.retain(|x| x.is_named(journal_name));
+        if journals.is_empty() {
+            eprintln!("{}", text_with("journal_not_found", &[&journal_name]).red());
+            return Err(());
+        }
+    }
+
+    stu::write_journals(&journals, out_path)?;
+    println!("{}", messages::exported(journals.len(), out_path).green());
+    Ok(())
+}
+
+fn open_resource(store: &mut JournalStore, uid: &str) -> Result<(), ()> {
+    let uid = &stu::resolve_uid_prefix(&store.journals, uid)?;
+    let (journal_index, log_index) =
+        stu::find_log_mut(&mut store.journals, uid).ok_or_else(|| {
+            eprintln!("{}", text_with("log_not_found", &[&uid]).red());
+        })?;
+
+    match &store.journals[journal_index].logs[log_index].resource {
+        Some(url) => open_url(url),
+        None => {
+            eprintln!(
+                "{}: {}",
+                text("error").red(),
+                text_with("no_resource", &[&uid, &uid])
+            );
+            Err(())
+        }
+    }
+}
+
+fn open(filepath: &Path, dir: bool) -> Result<(), ()> {
+    if dir {
+        return open_dir(utils::data_dir_of(filepath));
+    }
+
+    check_editor()?;
+    edit_text(filepath)?;
+
+    if JournalStore::load(filepath).is_err() {
+        eprintln!(
+            "{}: {}",
+            text("warning").yellow(),
+            text_with("data_file_invalid", &[&filepath.display()])
+        );
+        return Err(());
+    }
+    Ok(())
+}
@@ -0,0 +1,270 @@
+use crate::stu::messages::{text, text_with};
+use crate::stu::set_trust_stored_percentage;
+use crate::stu::utils::{
+    data_dir, parse_color, set_date_format, set_rounding_mode, set_subject_colors, DateFormat,
+    RoundingMode,
+};
+use colored::Colorize;
+use serde::{de, Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::result::Result;
+
+pub const CONFIG_FILE: &str = "config.toml";
+pub const TAG_RULES_FILE: &str = "tag-rules.toml";
+
+// Defaults read from `config.toml` in the data directory, flags passed on the
+// command line still win over these. Keys left out keep their default
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub editor: Option<String>,
+    pub sort: bool,
+    pub color: bool,
+    pub data_path: PathBuf,
+    #[serde(deserialize_with = "deserialize_rounding_mode")]
+    pub round: RoundingMode,
+    // How dates are shown and given to `--date`, `STU_DATE_FORMAT` wins over it
+    #[serde(deserialize_with = "deserialize_date_format")]
+    pub date_format: DateFormat,
+    // Journal for `stu add` without a name, `STU_DEFAULT_JOURNAL` wins over it
+    pub default_journal: Option<String>,
+    // Lowercased subject to color name, from the `[subject_colors]` section
+    pub subject_colors: HashMap<String, String>,
+    // Keep the percentage stored with each log instead of recomputing it from
+    // the counts, for hand set scores. Off by default so edited counts are
+    // never shown with a stale percentage
+    pub trust_stored_percentage: bool,
+    // Same as passing `--open-after-add` to every `add`
+    pub open_after_add: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            editor: None,
+            sort: false,
+            color: true,
+            data_path: data_dir().join("data.json"),
+            round: RoundingMode::Nearest,
+            date_format: DateFormat::Mdy,
+            default_journal: None,
+            subject_colors: HashMap::new(),
+            trust_stored_percentage: false,
+            open_after_add: false,
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        data_dir().join(CONFIG_FILE)
+    }
+
+    pub fn load() -> Result<Self, ()> {
+        let path = Config::path();
+
+        let mut config = if path.exists() {
+            let content = std::fs::read_to_string(&path).map_err(|err| {
+                eprintln!(
+                    "{}: {}",
+                    text("error").red(),
+                    text_with("read_config_failed", &[&path.display(), &err])
+                );
+            })?;
+            Config::parse(&content)?
+        } else {
+            Config::default()
+        };
+
+        // Points a single run at another data file, handy for scripts and tests
+        if let Some(data_path) = std::env::var_os("STU_DATA").filter(|x| !x.is_empty()) {
+            config.data_path = PathBuf::from(data_path);
+        }
+        if let Some(journal) = std::env::var("STU_DEFAULT_JOURNAL")
+            .ok()
+            .filter(|x| !x.is_empty())
+        {
+            config.default_journal = Some(journal);
+        }
+        if let Some(name) = std::env::var("STU_DATE_FORMAT")
+            .ok()
+            .filter(|x| !x.is_empty())
+        {
+            config.date_format = DateFormat::from_name(&name.to_lowercase()).ok_or_else(|| {
+                eprintln!(
+                    "{}: {}",
+                    text("error").red(),
+                    text_with("unknown_date_format", &[&name])
+                );
+            })?;
+        }
+
+        Ok(config)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, ()> {
+        let mut config: Config = toml::from_str(content).map_err(|err| {
+            eprintln!(
+                "{}: {}",
+                text("error").red(),
+                text_with("invalid_config", &[&err.to_string().trim_end()])
+            );
+        })?;
+
+        let mut subject_colors = HashMap::new();
+        for (subject, color) in config.subject_colors {
+            if parse_color(&color).is_none() {
+                eprintln!(
+                    "{}: {}",
+                    text("error").red(),
+                    text_with("unknown_subject_color", &[&color, &subject])
+                );
+                return Err(());
+            }
+            subject_colors.insert(subject.to_lowercase(), color.to_lowercase());
+        }
+        config.subject_colors = subject_colors;
+
+        Ok(config)
+    }
+
+    pub fn apply(&self) {
+        if let Some(editor) = &self.editor {
+            // `edit` looks at $VISUAL before $EDITOR
+            std::env::set_var("VISUAL", editor);
+        }
+        if !self.color {
+            colored::control::set_override(false);
+        }
+        set_rounding_mode(self.round);
+        set_date_format(self.date_format);
+        set_subject_colors(self.subject_colors.clone());
+        set_trust_stored_percentage(self.trust_stored_percentage);
+    }
+
+    pub fn show(&self) {
+        let editor = match &self.editor {
+            Some(editor) => editor.clone(),
+            None => edit::get_editor()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+        };
+
+        println!("editor = {}", quote(&editor));
+        println!("sort = {}", self.sort);
+        println!("color = {}", self.color);
+        println!(
+            "data_path = {}",
+            quote(&self.data_path.display().to_string())
+        );
+        println!("round = {}", quote(self.round.name()));
+        println!("date_format = {}", quote(self.date_format.name()));
+        println!("trust_stored_percentage = {}", self.trust_stored_percentage);
+        println!("open_after_add = {}", self.open_after_add);
+        if let Some(journal) = &self.default_journal {
+            println!("default_journal = {}", quote(journal));
+        }
+
+        if !self.subject_colors.is_empty() {
+            println!("\n[subject_colors]");
+            let mut colors: Vec<_> = self.subject_colors.iter().collect();
+            colors.sort();
+            for (subject, color) in colors {
+                println!("{} = {}", quote(subject), quote(color));
+            }
+        }
+    }
+}
+
+// `keyword = "tag"` keys, logs whose subject or topic contain the keyword get
+// the tag when created. Rules keep the order of the file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagRules {
+    pub rules: Vec<(String, String)>,
+}
+
+impl TagRules {
+    pub fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(TAG_RULES_FILE)
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, ()> {
+        let path = TagRules::path(data_dir);
+
+        if !path.exists() {
+            return Ok(TagRules::default());
+        }
+
+        let content = std::fs::read_to_string(&path).map_err(|err| {
+            eprintln!(
+                "{}: {}",
+                text("error").red(),
+                text_with("read_config_failed", &[&path.display(), &err])
+            );
+        })?;
+
+        TagRules::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self, ()> {
+        let invalid = |err: &dyn std::fmt::Display| {
+            eprintln!(
+                "{}: {}",
+                text("error").red(),
+                text_with("invalid_tag_rules", &[err])
+            );
+        };
+        let table: toml::Table =
+            toml::from_str(content).map_err(|err| invalid(&err.to_string().trim_end()))?;
+        let mut rules = TagRules::default();
+
+        for (keyword, tag) in table {
+            match tag.as_str() {
+                Some(tag) if !keyword.trim().is_empty() && !tag.is_empty() => {
+                    rules.rules.push((keyword.to_lowercase(), tag.to_string()))
+                }
+                _ => {
+                    invalid(&keyword);
+                    return Err(());
+                }
+            }
+        }
+
+        Ok(rules)
+    }
+
+    // Tags whose keyword shows up in any of the given fields, without repeats
+    pub fn tags_for(&self, fields: &[&str]) -> Vec<String> {
+        let fields: Vec<String> = fields.iter().map(|field| field.to_lowercase()).collect();
+        let mut tags: Vec<String> = Vec::new();
+
+        for (keyword, tag) in self.rules.iter() {
+            if fields.iter().any(|field| field.contains(keyword.as_str())) && !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+
+        tags
+    }
+}
+
+fn deserialize_rounding_mode<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<RoundingMode, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    RoundingMode::from_name(&name)
+        .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&name), &"a rounding mode"))
+}
+
+fn deserialize_date_format<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<DateFormat, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    DateFormat::from_name(&name)
+        .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&name), &"a date format"))
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,617 @@
+use std::env;
+use std::fmt::Display;
+use std::path::Path;
+
+type Table = &'static [(&'static str, &'static str)];
+
+// Every user-facing string lives here, keyed by a short identifier. Adding a
+// language is just a matter of writing a new table and listing it in
+// `LANGUAGES`, keys missing from it fall back to english.
+static LANGUAGES: &[(&str, Table)] = &[("en", EN), ("pt", PT)];
+
+static EN: Table = &[
+    ("error", "ERROR"),
+    // Success
+    ("created_journal", "Successfully created journal"),
+    ("added_log", "Successfully added log into {}"),
+    ("imported_logs", "Successfully imported {} logs into {}"),
+    ("exported", "Successfully exported {} journals to {}"),
+    ("wrote_output", "Successfully wrote the tables to {}"),
+    ("copied_tables", "Successfully copied the tables to the clipboard"),
+    ("copied_log", "Successfully copied log {} to the clipboard"),
+    ("removed_journal", "Successfully removed {} journal"),
+    ("pruned_journals", "Successfully removed {} empty journals"),
+    ("no_empty_journals", "There are no empty journals"),
+    ("remove_empty_confirm", "Remove the empty journals {}?"),
+    ("purge_confirm", "Delete every journal and log in {}?"),
+    ("purge_confirm_again", "This can't be undone from stu, are you sure?"),
+    ("purge_type_delete", "Type DELETE to confirm:"),
+    ("purge_cancelled", "Purge cancelled, nothing was deleted"),
+    ("purged", "Successfully deleted every journal, the old data was kept as {}"),
+    ("save_changes_confirm", "Save these changes?"),
+    ("removed_log", "Successfully removed log with {} UID"),
+    ("removed_logs_partly", "Removed {} of {} logs, the others were not found"),
+    ("no_logs_removed", "None of the given UIDs matched a log, nothing was removed"),
+    ("edited_log", "Successfully edited log with {} UID"),
+    ("edited_journal", "Successfully edited {} logs in {}"),
+    ("merged_journals", "Successfully merged {} logs from {} into {}"),
+    ("merged_logs", "Successfully merged log {} into {}"),
+    ("renamed", "Successfully renamed {} to {} in {} logs"),
+    ("archived_log", "Successfully archived log {}"),
+    ("unarchived_log", "Successfully unarchived log {}"),
+    ("already_archived", "Log {} is already archived"),
+    ("not_archived", "Log {} is not archived"),
+    ("goal_set", "Successfully set a {}% goal for {}"),
+    ("color_set", "Successfully set the {} color for {}"),
+    ("color_cleared", "Successfully cleared the color of {}"),
+    ("alias_added", "Successfully added the {} alias to {}"),
+    ("alias_removed", "Successfully removed the {} alias from {}"),
+    ("created_template", "Successfully created template at {}"),
+    ("nothing_to_review", "Nothing to review today"),
+    ("nothing_to_plan", "Nothing to plan, every topic was last studied at 100%"),
+    ("days_ago_today", "today"),
+    ("days_ago_one", "1 day ago"),
+    ("days_ago", "{} days ago"),
+    ("no_logs_yet", "(no logs yet)"),
+    ("result_count_one", "1 result"),
+    ("result_count", "{} results"),
+    // Errors
+    ("subcommand_needed", "Subcommand is needed"),
+    ("unexpected_subcommand", "Unexpected subcommand: {}"),
+    ("unknown_argument", "Unknown argument `{}`, run `stu --help` to see the usage"),
+    ("missing_argument", "Missing argument, run `stu --help` to see the usage"),
+    ("unknown_query_type", "Unknown query type"),
+    ("edit_needs_uid", "`--edit` only works when getting a single log by its UID"),
+    ("unknown_format", "Unknown format `{}`, expected one of: table, compact, wide"),
+    ("filter_value_missing", "`{}` needs a value"),
+    ("compact_needs_metrics", "`--compact` only works together with `-m`"),
+    ("unknown_mean", "Unknown mean `{}`, expected weighted or simple"),
+    ("mean_needs_metrics", "`--mean` only changes the metrics, use it with `-m`"),
+    ("no_matching_logs", "No logs match the filters"),
+    ("rename_empty", "The new name can't be empty"),
+    ("rename_needs_two", "rename-subject needs the old and the new name"),
+    ("no_missing_dates", "Every log has a date"),
+    ("invalid_count", "Count must be a positive whole number, got `{}`"),
+    ("unknown_style", "Unknown style `{}`, expected one of: rounded, ascii, markdown, none"),
+    ("unknown_sort_field", "Unknown sort `{}`, expected one of {} optionally followed by `:asc` or `:desc`"),
+    ("unknown_journal_sort", "Unknown journal order `{}`, expected one of {}"),
+    ("unknown_granularity", "Unknown period `{}`, expected one of: week, month"),
+    ("by_needs_histogram", "`--by` only applies to `stu stats --histogram`"),
+    ("by_topic_needs_journal", "`--by-topic` applies to one journal without `--histogram`, use `stu stats -j <name> --by-topic`"),
+    ("no_dated_logs", "There's no logs with a known date"),
+    ("unknown_today", "Could not read today's date"),
+    ("query_not_provided", "<query> was not provided"),
+    ("query_empty", "<query> cannot be empty"),
+    ("no_journals", "There's no journals at the moment, create one with the command `stu add -j <name>`"),
+    ("no_logs", "There are no logs yet, add one with `stu add <journal>`"),
+    ("journal_name_not_provided", "Journal name was not provided"),
+    ("merge_same_journal", "Cannot merge a journal into itself"),
+    ("merge_same_log", "Cannot merge a log into itself"),
+    ("merge_logs_needs_two", "Expected the UIDs of the two logs to merge"),
+    ("merge_logs_different", "Logs {} and {} have a different subject or topic, use --force to merge them anyway"),
+    ("merge_skipped", "{} logs of {} share a UID with a log in {} and were kept"),
+    ("journal_name_empty", "Journal name cannot be empty"),
+    ("journal_name_dash", "Journal name `{}` cannot start with a dash"),
+    ("journal_name_control", "Journal name cannot contain control characters"),
+    ("journal_name_too_long", "Journal name cannot be longer than {} characters"),
+    ("invalid_goal", "Goal must be a percentage between 0 and 100, got `{}`"),
+    ("goal_not_provided", "Goal percentage was not provided"),
+    ("unknown_color", "Unknown color `{}`, expected one of: {} or none"),
+    ("color_not_provided", "Color was not provided"),
+    ("alias_not_provided", "Alias was not provided"),
+    ("name_taken", "`{}` is already a journal name or alias"),
+    ("journal_exists", "There is already a journal named `{}`"),
+    ("alias_not_found", "Journal {} has no `{}` alias"),
+    ("note_not_provided", "Note text was not provided"),
+    ("journal_name_not_provided_hint", "Journal name was not provided, run `stu show` to list available journals"),
+    ("journal_not_found", "Journal with <{}> name not found"),
+    ("journal_not_found_create", "Journal with the name `{}` was not found, do you want to create one? "),
+    ("log_not_found", "Log with <{}> name not found"),
+    ("log_name_not_provided", "log name was not provided"),
+    ("uid_not_provided", "UID was not provided"),
+    ("uid_not_found", "log with <{}> UID not found"),
+    ("ambiguous_uid_prefix", "The UID prefix <{}> matches {} logs: {}, type more of it"),
+    ("invalid_uid", "Argument is not a valid UID"),
+    ("query_failed", "unsuccessfully <{}> query"),
+    ("serialize_failed", "Could not parse journal struct into json file: {}"),
+    ("read_json_failed", "Could not read json filepath {}"),
+    ("read_config_failed", "Could not read config file {}: {}"),
+    ("invalid_config", "Invalid config file: {}"),
+    ("unknown_subject_color", "Unknown color `{}` for subject `{}` in config file"),
+    ("no_subject_colors", "No subject colors, add them under [subject_colors] in config.toml"),
+    ("invalid_tag_rules", "Invalid tag rules file, expected `keyword = \"tag\"`: {}"),
+    ("tag_not_provided", "Tag was not provided"),
+    ("tag_query_failed", "No logs tagged <{}>"),
+    ("parse_json_failed", "Could not create json object from string: {}"),
+    ("invalid_data_file", "{} must be a JSON object with `schema` and `journals`, or a bare array of journals from older versions"),
+    ("newer_schema", "{} uses data schema {}, which is newer than this stu understands, update stu to read it"),
+    ("migrated_data", "Upgraded {} from data schema {} to {}, the old file was kept as {}"),
+    ("data_damaged", "{} is not valid JSON anymore, the newest backup that is valid is {}"),
+    ("restore_backup_prompt", "Restore the data file from that backup? The damaged file is kept"),
+    ("restore_backup_hint", "{} is not valid JSON anymore, to restore it copy {} over it"),
+    ("restored_backup", "Restored {} from {}, the damaged file was kept as {}"),
+    ("duplicate_journal", "There are {} journals named `{}`, commands will refuse that name until one is renamed with `stu open`"),
+    ("ambiguous_journal", "`{}` matches {} journals, rename one of them with `stu open` first"),
+    ("coerced_log_field", "`{}` of log {} in {} was {}, read as {}"),
+    ("skipped_log", "Skipped log {} in {}, it could not be read: {}"),
+    ("read_log_file_failed", "Failed to read log file: {} {} at line {}"),
+    ("not_whole_number", "{} must be a whole number, got '{}'"),
+    ("read_import_file_failed", "Could not read file {}: {}"),
+    ("invalid_import_row", "Invalid row at line {}, expected `subject,topic,questions,right`: {}"),
+    ("empty_import_file", "No logs found in {}"),
+    ("import_file_not_provided", "File path was not provided"),
+    ("log_source_twice", "`{}` can't be combined with another log source"),
+    ("read_stdin_failed", "Could not read the note from stdin: {}"),
+    ("date_not_provided", "Date was not provided, expected `{}`"),
+    ("date_pattern_mdy", "MM/DD/YYYY"),
+    ("date_pattern_dmy", "DD/MM/YYYY"),
+    ("date_pattern_iso", "YYYY-MM-DD"),
+    ("invalid_date", "Invalid date `{}`, expected a real date as `{}`"),
+    ("unknown_date_format", "Unknown date format `{}`, expected one of mdy, dmy, iso"),
+    ("diff_file_not_provided", "File to compare with was not provided"),
+    ("export_path_not_provided", "Output path was not provided, use `--json <path>`"),
+    ("diff_journal", "journal {}"),
+    ("diff_log", "log {} ({}/{}) in {}"),
+    ("diff_changed", "log {} in {}, {}"),
+    ("no_differences", "No differences"),
+    ("field_unchanged", "a field was left unchanged, log was not added"),
+    ("field_unchanged_named", "the {} field was left unchanged, log was not saved"),
+    ("field_left_blank", "the {} field was left blank, log was not saved"),
+    ("no_changes", "No changes, log discarded"),
+    ("edit_interrupted", "Editing aborted"),
+    ("draft_saved", "Your note was kept as a draft, it will be offered the next time you add a log to `{}`"),
+    ("resume_draft", "There is an unfinished note for this journal, resume it?"),
+    ("save_draft_failed", "Could not save the draft to {}: {}"),
+    ("unknown_block_uid", "Found a block with unknown UID <{}>, changes were not saved"),
+    ("create_tempfile_failed", "Could not create tempfile: {}"),
+    ("delete_tempfile_failed", "Could not delete temporary file: {}"),
+    ("create_file_failed", "Could not create file: {}"),
+    ("write_output_failed", "Could not write to {}: {}"),
+    ("template_exists", "Template already exists at {}"),
+    ("template_from_file", "Template from {}"),
+    ("template_builtin", "Built-in template, write your own with `stu template --init`"),
+    ("template_example_journal", "example"),
+    ("sync_failed", "Could not sync OS data: {}"),
+    ("edit_file_failed", "Could not edit file: {}"),
+    ("no_editor", "No editor found, set $EDITOR or install vim"),
+    ("non_interactive", "Not running in an interactive terminal, so no editor was opened. Pipe a note with `add <journal> --stdin`, import rows with `add <journal> --from-file <path>` or add notes with `edit <UID> --append-note <text>`"),
+    ("open_dir_failed", "Could not open directory {}: {}"),
+    ("open_url_failed", "Could not open {}: {}"),
+    ("no_clipboard", "Could not copy to the clipboard ({}), over SSH or without a display there is none to copy to"),
+    ("copy_with_out", "`--copy` and `--out` can't be used together"),
+    ("invalid_resource_url", "`{}` doesn't look like a link, only http:// and https:// links are opened"),
+    ("no_resource", "The log {} has no resource, add one with `stu edit {}`"),
+    ("data_file_invalid", "{} is no longer valid, fix it before using stu again"),
+    ("warning", "WARNING"),
+    ("create_database_failed", "Could not create database file: {}"),
+    // Tables
+    ("header_subject", "Subject"),
+    ("header_topic", "Topic"),
+    ("header_date", "Date"),
+    ("header_resource", "Resource"),
+    ("header_trend", "Trend"),
+    ("header_uid", "UID"),
+    ("header_questions", "Questions"),
+    ("header_right_answers", "Right answers"),
+    ("header_answers", "Answers"),
+    ("header_percentage", "Percentage"),
+    ("header_total", "Total"),
+    ("header_band", "Band"),
+    ("header_logs", "Logs"),
+    ("header_goal", "Goal"),
+    ("report_mean", "Mean percentage"),
+    ("report_best", "Best log"),
+    ("report_worst", "Worst log"),
+    ("header_notes", "Notes"),
+    ("header_tags", "Tags"),
+    ("header_journal", "Journal"),
+    ("review_journal", "Review"),
+    ("list_there_is", "There's"),
+    ("list_journals_in_database", "Journals in database"),
+    ("list_logs", "Logs"),
+    // Usage
+    ("usage_title", "Usage"),
+    ("usage_subcommands_title", "Subcommands"),
+    ("usage_editor", "Change editor with `EDITOR=emacs` for instance. Default editor is vim"),
+    ("usage_help", "    -h      --help                    print help"),
+    ("usage_version", "    -V      --version  version        print version"),
+    ("usage_lenient", "            --lenient                 ignore unused arguments, goes before the subcommand"),
+    ("usage_show", "    show   <subcommand>               print all user journals, use -m if you wanna print the metrics\n                │          ╰-------------> table format: \"--format <table|compact|wide>\"\n                │          ╰-------------> table borders: \"--style <rounded|ascii|markdown|none>\"\n                │          ╰-------------> sort logs by a field: \"--sort <subject|topic|date|questions|right|percentage>[:asc|:desc]\"\n                │          ╰-------------> order the journals themselves: \"--sort-journals <name|logs|percentage>\"\n                │          ╰-------------> reverse the log order, after sorting if any: \"--reverse\"\n                │          ╰-------------> color logs from red to green by percentage: \"--color-scale\"\n                │          ╰-------------> percentages over time under each journal: \"--sparkline\"\n                │          ╰-------------> every log in a single table: \"--all\"\n                │          ╰-------------> stable tab separated lines for scripts: \"--porcelain\"\n                │          ╰-------------> write the tables to a file, without colors: \"--out <path>\"\n                │          ╰-------------> copy the tables to the clipboard, without colors: \"--copy\"\n                │          ╰-------------> subject colors from `config.toml`: \"--legend\"\n                │          ╰-------------> only logs containing the text: \"--subject <text>\", \"--topic <text>\"\n                │          ╰-------------> only logs with an unknown date, to fix with `edit`: \"--missing-dates\"\n                │          ╰-------------> only logs from the last days, today included: \"--since-days <n>\"\n                │          ╰-------------> metrics of every journal in one table: \"-m --compact\"\n                │          ╰-------------> journal percentage, weighted sums right answers over questions so bigger sessions weigh more, simple averages the log percentages: \"-m --mean <weighted|simple>\"\n                ╰------------------------> print metrics: \"-m\""),
+    ("usage_add", "    add    <subcommand> <value>       add either a new log or journal\n                │          ╰-------------> import logs from a CSV/TSV: \"<journal> --from-file <path>\"\n                │          ╰-------------> read a filled in note from stdin: \"<journal> --stdin\"\n                │          ╰-------------> start from the subject and topic of another log: \"<journal> --from-uid <UID>\"\n                │          ╰-------------> date the new logs instead of today: \"<journal> --date <date>\"\n                │          ╰-------------> print the new logs once saved: \"<journal> --open-after-add\"\n                │          ╰-------------> goal for the new journal: \"-j <name> --goal <percentage>\"\n                │          ╰-------------> without a journal, uses $STU_DEFAULT_JOURNAL or `default_journal` from `config.toml`\n                ╰------------------------> add journal: \"-j\""),
+    ("usage_remove", "    remove <subcommand> <value>       remove a log with the given <value>\n                │          ╰-------------> value can be: [UID..., journal]\n                │          ╰-------------> remove journals without logs: \"--empty [--force]\"\n                ╰------------------------> remove journal: \"-j\""),
+    ("usage_get", "    get    <subcommand> <query>       search for <query> and print results\n                │          ╰-------------> query can be: [UID, journal, subject, topic, \"<date>\"]\n                │          ╰-------------> any UID can be cut short while no other UID starts the same, here and in remove, edit, merge-logs and open\n                │          ╰-------------> logs tagged by `tag-rules.toml`: \"--tag <tag>\"\n                │          ╰-------------> show a log and open it in the editor: \"<UID> --edit\"\n                │          ╰-------------> copy a log to the clipboard, as shown or as JSON: \"<UID> --copy\", \"<UID> --copy-json\"\n                │          ╰-------------> print only the number of matches: \"<query> --count-only\"\n                ╰------------------------> sort query: \"-s\""),
+    ("usage_edit", "    edit   <subcommand> <value>       edit log with the given UID\n                │          ╰-------------> add a dated note without the editor: \"<UID> --append-note <text>\"\n                │          ╰-------------> ask before saving the edited log: \"<UID> --confirm\"\n                ╰------------------------> edit every log of a journal: \"-j\""),
+    ("usage_last", "    last                              edit the most recently created log"),
+    ("usage_goal", "    goal   <journal> <percentage>     set a percentage goal for the journal, shown with \"show -m\""),
+    ("usage_color", "    color  <journal> <color>          set the color of the journal name in \"show\", \"none\" clears it"),
+    ("usage_alias", "    alias  <journal> <action> <short> short name accepted wherever a journal is expected\n                ╰------------------------> action can be: [add, remove]"),
+    ("usage_merge", "    merge  <source> <dest>            move every log of <source> into <dest> and remove <source>"),
+    ("usage_merge_logs", "    merge-logs <UID> <UID>            add the second log into the first, e.g. a session logged twice\n                ╰------------------------> merge logs with a different subject or topic: \"--force\""),
+    ("usage_rename_subject", "    rename-subject <old> <new>        rename a subject in every log, ignoring case\n                ╰------------------------> rename a topic instead: \"--topic\""),
+    ("usage_purge", "    purge                             delete every journal after confirming, a backup is kept"),
+    ("usage_archive", "    archive <UID>                     hide a log from show, get, stats and reviews without deleting it, export keeps it\n                │          ╰-------------> bring it back: \"unarchive <UID>\"\n                ╰------------------------> show or get archived logs too: \"--include-archived\""),
+    ("usage_stats", "    stats  <subcommand>               print statistics about the logs, logs per percentage band by default\n                │          ╰-------------> group by: \"--by <week|month>\"\n                │          ╰-------------> only one journal, with a detailed report: \"-j <name>\"\n                │          ╰-------------> percentage per topic of one journal, worst first: \"-j <name> --by-topic\"\n                ╰------------------------> logs per period: \"--histogram\""),
+    ("usage_review", "    review                            list logs due for review, sooner the lower their percentage"),
+    ("usage_recent", "    recent [N]                        print the N most recent logs across all journals, 10 by default"),
+    ("usage_weak", "    weak   [N]                        print the N lowest scoring logs across all journals, 5 by default"),
+    ("usage_plan", "    plan   [N]                        print the N topics most in need of review, by their last score and how long ago it was, 5 by default"),
+    ("usage_template", "    template <subcommand>             customize the note template, `template.txt` in the data directory\n                │          ╰-------------> print the note a new log starts from: \"--show\"\n                ╰------------------------> write the default template: \"--init\""),
+    ("usage_config", "    config <subcommand>               defaults for editor, sort, color, data path and rounding, `config.toml` in the data directory\n                ╰------------------------> print the effective config: \"--show\""),
+    ("usage_open", "    open   <subcommand>               edit the data file in your editor and validate it afterwards\n                │          ╰-------------> open the resource link of a log in the browser: \"<UID> --resource\"\n                ╰------------------------> open the data directory in the file manager: \"--dir\""),
+    ("usage_diff", "    diff   <other.json>               compare the data with another file, - only here, + only there, ~ changed"),
+    ("usage_export", "    export <subcommand>               write the journals to a file that can be read back as a data file\n                │          ╰-------------> output file: \"--json <path>\"\n                ╰------------------------> only one journal: \"-j <name>\""),
+];
+
+static PT: Table = &[
+    ("error", "ERRO"),
+    // Success
+    ("created_journal", "Diário criado com sucesso"),
+    ("added_log", "Registro adicionado com sucesso em {}"),
+    ("imported_logs", "{} registros importados com sucesso em {}"),
+    ("exported", "{} diários exportados com sucesso para {}"),
+    ("wrote_output", "Tabelas gravadas com sucesso em {}"),
+    ("copied_tables", "Tabelas copiadas com sucesso para a área de transferência"),
+    ("copied_log", "Registro {} copiado com sucesso para a área de transferência"),
+    ("removed_journal", "Diário {} removido com sucesso"),
+    ("pruned_journals", "{} diários vazios removidos com sucesso"),
+    ("no_empty_journals", "Não há diários vazios"),
+    ("remove_empty_confirm", "Remover os diários vazios {}?"),
+    ("purge_confirm", "Apagar todos os diários e registros em {}?"),
+    ("purge_confirm_again", "Isso não pode ser desfeito pelo stu, tem certeza?"),
+    ("purge_type_delete", "Digite DELETE para confirmar:"),
+    ("purge_cancelled", "Limpeza cancelada, nada foi apagado"),
+    ("purged", "Todos os diários foram apagados com sucesso, os dados antigos foram mantidos em {}"),
+    ("save_changes_confirm", "Salvar essas alterações?"),
+    ("removed_log", "Registro com UID {} removido com sucesso"),
+    ("removed_logs_partly", "{} de {} registros removidos, os outros não foram encontrados"),
+    ("no_logs_removed", "Nenhum dos UIDs informados corresponde a um registro, nada foi removido"),
+    ("edited_log", "Registro com UID {} editado com sucesso"),
+    ("edited_journal", "{} registros editados com sucesso em {}"),
+    ("merged_journals", "{} registros de {} mesclados com sucesso em {}"),
+    ("merged_logs", "Registro {} mesclado com sucesso em {}"),
+    ("renamed", "{} renomeado para {} em {} registros com sucesso"),
+    ("archived_log", "Registro {} arquivado com sucesso"),
+    ("unarchived_log", "Registro {} desarquivado com sucesso"),
+    ("already_archived", "O registro {} já está arquivado"),
+    ("not_archived", "O registro {} não está arquivado"),
+    ("goal_set", "Meta de {}% definida com sucesso para {}"),
+    ("color_set", "Cor {} definida com sucesso para {}"),
+    ("color_cleared", "Cor removida com sucesso de {}"),
+    ("alias_added", "Apelido {} adicionado com sucesso a {}"),
+    ("alias_removed", "Apelido {} removido com sucesso de {}"),
+    ("created_template", "Modelo criado com sucesso em {}"),
+    ("nothing_to_review", "Nada para revisar hoje"),
+    ("nothing_to_plan", "Nada para planejar, todo tópico foi estudado por último com 100%"),
+    ("days_ago_today", "hoje"),
+    ("days_ago_one", "há 1 dia"),
+    ("days_ago", "há {} dias"),
+    ("no_logs_yet", "(nenhum registro ainda)"),
+    ("result_count_one", "1 resultado"),
+    ("result_count", "{} resultados"),
+    // Errors
+    ("subcommand_needed", "É necessário informar um subcomando"),
+    ("unexpected_subcommand", "Subcomando inesperado: {}"),
+    ("unknown_argument", "Argumento desconhecido `{}`, rode `stu --help` para ver o uso"),
+    ("missing_argument", "Argumento faltando, rode `stu --help` para ver o uso"),
+    ("unknown_query_type", "Tipo de busca desconhecido"),
+    ("edit_needs_uid", "`--edit` só funciona ao buscar um único registro pelo UID"),
+    ("unknown_format", "Formato `{}` desconhecido, use um de: table, compact, wide"),
+    ("filter_value_missing", "`{}` precisa de um valor"),
+    ("compact_needs_metrics", "`--compact` só funciona junto com `-m`"),
+    ("unknown_mean", "Média desconhecida `{}`, esperado weighted ou simple"),
+    ("mean_needs_metrics", "`--mean` só muda as métricas, use junto com `-m`"),
+    ("no_matching_logs", "Nenhum registro corresponde aos filtros"),
+    ("rename_empty", "O novo nome não pode ser vazio"),
+    ("rename_needs_two", "rename-subject precisa do nome antigo e do novo"),
+    ("no_missing_dates", "Todos os registros têm uma data"),
+    ("invalid_count", "A quantidade precisa ser um número inteiro positivo, recebido `{}`"),
+    ("unknown_style", "Estilo `{}` desconhecido, use um de: rounded, ascii, markdown, none"),
+    ("unknown_sort_field", "Ordenação desconhecida `{}`, esperado um de {} opcionalmente seguido de `:asc` ou `:desc`"),
+    ("unknown_journal_sort", "Ordem de diários desconhecida `{}`, esperado um de {}"),
+    ("unknown_granularity", "Período `{}` desconhecido, use um de: week, month"),
+    ("by_needs_histogram", "`--by` só se aplica a `stu stats --histogram`"),
+    ("by_topic_needs_journal", "`--by-topic` se aplica a um diário sem `--histogram`, use `stu stats -j <nome> --by-topic`"),
+    ("no_dated_logs", "Não há registros com data conhecida"),
+    ("unknown_today", "Não foi possível ler a data de hoje"),
+    ("query_not_provided", "<busca> não foi informada"),
+    ("query_empty", "<busca> não pode ser vazia"),
+    ("no_journals", "Não há diários no momento, crie um com o comando `stu add -j <nome>`"),
+    ("no_logs", "Ainda não há registros, adicione um com `stu add <diário>`"),
+    ("journal_name_not_provided", "O nome do diário não foi informado"),
+    ("merge_same_journal", "Não é possível mesclar um diário com ele mesmo"),
+    ("merge_same_log", "Não é possível mesclar um registro com ele mesmo"),
+    ("merge_logs_needs_two", "Esperado os UIDs dos dois registros a mesclar"),
+    ("merge_logs_different", "Os registros {} e {} têm matéria ou tópico diferentes, use --force para mesclar mesmo assim"),
+    ("merge_skipped", "{} registros de {} têm o mesmo UID de um registro em {} e foram mantidos"),
+    ("journal_name_empty", "O nome do diário não pode ser vazio"),
+    ("journal_name_dash", "O nome do diário `{}` não pode começar com um traço"),
+    ("journal_name_control", "O nome do diário não pode conter caracteres de controle"),
+    ("journal_name_too_long", "O nome do diário não pode ter mais de {} caracteres"),
+    ("invalid_goal", "A meta deve ser uma porcentagem entre 0 e 100, recebido `{}`"),
+    ("goal_not_provided", "A porcentagem da meta não foi informada"),
+    ("unknown_color", "Cor `{}` desconhecida, use uma de: {} ou none"),
+    ("color_not_provided", "A cor não foi informada"),
+    ("alias_not_provided", "O apelido não foi informado"),
+    ("name_taken", "`{}` já é o nome ou apelido de um diário"),
+    ("journal_exists", "Já existe um diário chamado `{}`"),
+    ("alias_not_found", "O diário {} não tem o apelido `{}`"),
+    ("note_not_provided", "O texto da nota não foi informado"),
+    ("journal_name_not_provided_hint", "O nome do diário não foi informado, rode `stu show` para listar os diários disponíveis"),
+    ("journal_not_found", "Diário com o nome <{}> não encontrado"),
+    ("journal_not_found_create", "Diário com o nome `{}` não foi encontrado, deseja criar um? "),
+    ("log_not_found", "Registro <{}> não encontrado"),
+    ("log_name_not_provided", "o nome do registro não foi informado"),
+    ("uid_not_provided", "UID não foi informado"),
+    ("uid_not_found", "registro com UID <{}> não encontrado"),
+    ("ambiguous_uid_prefix", "O prefixo de UID <{}> corresponde a {} registros: {}, digite mais dele"),
+    ("invalid_uid", "Argumento não é um UID válido"),
+    ("query_failed", "busca por <{}> sem resultados"),
+    ("serialize_failed", "Não foi possível converter os diários para json: {}"),
+    ("read_json_failed", "Não foi possível ler o arquivo json {}"),
+    ("read_config_failed", "Não foi possível ler o arquivo de configuração {}: {}"),
+    ("invalid_config", "Arquivo de configuração inválido: {}"),
+    ("unknown_subject_color", "Cor `{}` desconhecida para a matéria `{}` no arquivo de configuração"),
+    ("no_subject_colors", "Nenhuma cor de assunto, adicione em [subject_colors] no config.toml"),
+    ("invalid_tag_rules", "Arquivo de regras de etiquetas inválido, esperado `palavra = \"etiqueta\"`: {}"),
+    ("tag_not_provided", "A etiqueta não foi informada"),
+    ("tag_query_failed", "Nenhum registro com a etiqueta <{}>"),
+    ("parse_json_failed", "Não foi possível criar o objeto json a partir do texto: {}"),
+    ("invalid_data_file", "{} precisa ser um objeto JSON com `schema` e `journals`, ou uma lista de diários de versões antigas"),
+    ("newer_schema", "{} usa o esquema de dados {}, mais novo do que este stu entende, atualize o stu para lê-lo"),
+    ("migrated_data", "{} atualizado do esquema de dados {} para {}, o arquivo antigo foi mantido como {}"),
+    ("data_damaged", "{} não é mais um JSON válido, o backup válido mais recente é {}"),
+    ("restore_backup_prompt", "Restaurar o arquivo de dados a partir desse backup? O arquivo danificado é mantido"),
+    ("restore_backup_hint", "{} não é mais um JSON válido, para restaurá-lo copie {} sobre ele"),
+    ("restored_backup", "{} restaurado a partir de {}, o arquivo danificado foi mantido como {}"),
+    ("duplicate_journal", "Existem {} diários chamados `{}`, os comandos vão recusar esse nome até um ser renomeado com `stu open`"),
+    ("ambiguous_journal", "`{}` corresponde a {} diários, renomeie um deles com `stu open` antes"),
+    ("coerced_log_field", "`{}` do registro {} em {} era {}, lido como {}"),
+    ("skipped_log", "Registro {} em {} ignorado, não foi possível lê-lo: {}"),
+    ("read_log_file_failed", "Falha ao ler o arquivo de registro: {} {} na linha {}"),
+    ("not_whole_number", "{} deve ser um número inteiro, recebido '{}'"),
+    ("read_import_file_failed", "Não foi possível ler o arquivo {}: {}"),
+    ("invalid_import_row", "Linha {} inválida, esperado `matéria,tópico,questões,acertos`: {}"),
+    ("empty_import_file", "Nenhum registro encontrado em {}"),
+    ("import_file_not_provided", "O caminho do arquivo não foi informado"),
+    ("log_source_twice", "`{}` não pode ser combinado com outra origem de registros"),
+    ("read_stdin_failed", "Não foi possível ler a nota da entrada padrão: {}"),
+    ("date_not_provided", "A data não foi informada, esperado `{}`"),
+    ("date_pattern_mdy", "MM/DD/AAAA"),
+    ("date_pattern_dmy", "DD/MM/AAAA"),
+    ("date_pattern_iso", "AAAA-MM-DD"),
+    ("invalid_date", "Data inválida `{}`, esperado uma data real como `{}`"),
+    ("unknown_date_format", "Formato de data desconhecido `{}`, esperado um de mdy, dmy, iso"),
+    ("diff_file_not_provided", "O arquivo para comparar não foi informado"),
+    ("export_path_not_provided", "O caminho de destino não foi informado, use `--json <caminho>`"),
+    ("diff_journal", "diário {}"),
+    ("diff_log", "registro {} ({}/{}) em {}"),
+    ("diff_changed", "registro {} em {}, {}"),
+    ("no_differences", "Nenhuma diferença"),
+    ("field_unchanged", "um campo não foi alterado, o registro não foi adicionado"),
+    ("field_unchanged_named", "o campo {} não foi alterado, o registro não foi salvo"),
+    ("field_left_blank", "o campo {} foi deixado em branco, o registro não foi salvo"),
+    ("no_changes", "Nenhuma alteração, registro descartado"),
+    ("edit_interrupted", "Edição cancelada"),
+    ("draft_saved", "Sua nota foi guardada como rascunho, ela será oferecida na próxima vez que adicionar um registro em `{}`"),
+    ("resume_draft", "Existe uma nota inacabada para este diário, continuar?"),
+    ("save_draft_failed", "Não foi possível salvar o rascunho em {}: {}"),
+    ("unknown_block_uid", "Bloco com UID desconhecido <{}>, as alterações não foram salvas"),
+    ("create_tempfile_failed", "Não foi possível criar o arquivo temporário: {}"),
+    ("delete_tempfile_failed", "Não foi possível apagar o arquivo temporário: {}"),
+    ("create_file_failed", "Não foi possível criar o arquivo: {}"),
+    ("write_output_failed", "Não foi possível gravar em {}: {}"),
+    ("template_exists", "O modelo já existe em {}"),
+    ("template_from_file", "Modelo de {}"),
+    ("template_builtin", "Modelo padrão, crie o seu com `stu template --init`"),
+    ("template_example_journal", "exemplo"),
+    ("sync_failed", "Não foi possível sincronizar os dados com o sistema: {}"),
+    ("edit_file_failed", "Não foi possível editar o arquivo: {}"),
+    ("no_editor", "Nenhum editor encontrado, defina $EDITOR ou instale o vim"),
+    ("non_interactive", "Não está rodando em um terminal interativo, então nenhum editor foi aberto. Envie uma nota com `add <diário> --stdin`, importe linhas com `add <diário> --from-file <caminho>` ou adicione notas com `edit <UID> --append-note <texto>`"),
+    ("open_dir_failed", "Não foi possível abrir o diretório {}: {}"),
+    ("open_url_failed", "Não foi possível abrir {}: {}"),
+    ("no_clipboard", "Não foi possível copiar para a área de transferência ({}), via SSH ou sem tela não há uma para copiar"),
+    ("copy_with_out", "`--copy` e `--out` não podem ser usados juntos"),
+    ("invalid_resource_url", "`{}` não parece um link, só links http:// e https:// são abertos"),
+    ("no_resource", "O registro {} não tem recurso, adicione um com `stu edit {}`"),
+    ("data_file_invalid", "{} não é mais válido, corrija-o antes de usar o stu de novo"),
+    ("warning", "AVISO"),
+    ("create_database_failed", "Não foi possível criar o banco de dados: {}"),
+    // Tables
+    ("header_subject", "Matéria"),
+    ("header_topic", "Tópico"),
+    ("header_date", "Data"),
+    ("header_resource", "Recurso"),
+    ("header_trend", "Tendência"),
+    ("header_uid", "UID"),
+    ("header_questions", "Questões"),
+    ("header_right_answers", "Acertos"),
+    ("header_answers", "Acertos"),
+    ("header_percentage", "Porcentagem"),
+    ("header_total", "Total"),
+    ("header_band", "Faixa"),
+    ("header_logs", "Registros"),
+    ("header_goal", "Meta"),
+    ("report_mean", "Porcentagem média"),
+    ("report_best", "Melhor registro"),
+    ("report_worst", "Pior registro"),
+    ("header_notes", "Notas"),
+    ("header_tags", "Etiquetas"),
+    ("header_journal", "Diário"),
+    ("review_journal", "Revisão"),
+    ("list_there_is", "Há"),
+    ("list_journals_in_database", "diários no banco de dados"),
+    ("list_logs", "Registros"),
+    // Usage
+    ("usage_title", "Uso"),
+    ("usage_subcommands_title", "Subcomandos"),
+    ("usage_editor", "Troque o editor com `EDITOR=emacs`, por exemplo. O editor padrão é o vim"),
+    ("usage_help", "    -h      --help                    mostra a ajuda"),
+    ("usage_version", "    -V      --version  version        mostra a versão"),
+    ("usage_lenient", "            --lenient                 ignora argumentos não usados, vem antes do subcomando"),
+    ("usage_show", "    show   <subcomando>               mostra todos os diários, use -m para mostrar as métricas\n                │          ╰-------------> formato da tabela: \"--format <table|compact|wide>\"\n                │          ╰-------------> bordas da tabela: \"--style <rounded|ascii|markdown|none>\"\n                │          ╰-------------> ordena os registros por um campo: \"--sort <subject|topic|date|questions|right|percentage>[:asc|:desc]\"\n                │          ╰-------------> ordena os próprios diários: \"--sort-journals <name|logs|percentage>\"\n                │          ╰-------------> inverte a ordem dos registros, depois de ordenar: \"--reverse\"\n                │          ╰-------------> colore os registros de vermelho a verde pela porcentagem: \"--color-scale\"\n                │          ╰-------------> porcentagens ao longo do tempo abaixo de cada diário: \"--sparkline\"\n                │          ╰-------------> todos os registros em uma única tabela: \"--all\"\n                │          ╰-------------> linhas estáveis separadas por tab para scripts: \"--porcelain\"\n                │          ╰-------------> grava as tabelas em um arquivo, sem cores: \"--out <caminho>\"\n                │          ╰-------------> copia as tabelas para a área de transferência, sem cores: \"--copy\"\n                │          ╰-------------> cores dos assuntos do `config.toml`: \"--legend\"\n                │          ╰-------------> só registros contendo o texto: \"--subject <texto>\", \"--topic <texto>\"\n                │          ╰-------------> só registros com data desconhecida, para corrigir com `edit`: \"--missing-dates\"\n                │          ╰-------------> só registros dos últimos dias, incluindo hoje: \"--since-days <n>\"\n                │          ╰-------------> métricas de todos os diários em uma tabela: \"-m --compact\"\n                │          ╰-------------> porcentagem do diário, weighted soma acertos sobre questões e sessões maiores pesam mais, simple tira a média das porcentagens dos registros: \"-m --mean <weighted|simple>\"\n                ╰------------------------> mostrar métricas: \"-m\""),
+    ("usage_add", "    add    <subcomando> <valor>       adiciona um novo registro ou diário\n                │          ╰-------------> importar registros de um CSV/TSV: \"<diário> --from-file <caminho>\"\n                │          ╰-------------> ler uma nota preenchida da entrada padrão: \"<diário> --stdin\"\n                │          ╰-------------> começar com a matéria e o tópico de outro registro: \"<diário> --from-uid <UID>\"\n                │          ╰-------------> datar os novos registros em vez de hoje: \"<diário> --date <data>\"\n                │          ╰-------------> mostrar os novos registros depois de salvos: \"<diário> --open-after-add\"\n                │          ╰-------------> meta do novo diário: \"-j <nome> --goal <porcentagem>\"\n                │          ╰-------------> sem um diário, usa $STU_DEFAULT_JOURNAL ou `default_journal` do `config.toml`\n                ╰------------------------> adicionar diário: \"-j\""),
+    ("usage_remove", "    remove <subcomando> <valor>       remove um registro com o <valor> informado\n                │          ╰-------------> valor pode ser: [UID..., diário]\n                │          ╰-------------> remover diários sem registros: \"--empty [--force]\"\n                ╰------------------------> remover diário: \"-j\""),
+    ("usage_get", "    get    <subcomando> <busca>       procura por <busca> e mostra os resultados\n                │          ╰-------------> busca pode ser: [UID, diário, matéria, tópico, \"<data>\"]\n                │          ╰-------------> qualquer UID pode ser encurtado enquanto nenhum outro UID começar igual, aqui e em remove, edit, merge-logs e open\n                │          ╰-------------> registros etiquetados por `tag-rules.toml`: \"--tag <etiqueta>\"\n                │          ╰-------------> mostra um registro e abre no editor: \"<UID> --edit\"\n                │          ╰-------------> copia um registro para a área de transferência, como mostrado ou em JSON: \"<UID> --copy\", \"<UID> --copy-json\"\n                │          ╰-------------> mostra só o número de resultados: \"<busca> --count-only\"\n                ╰------------------------> ordenar busca: \"-s\""),
+    ("usage_edit", "    edit   <subcomando> <valor>       edita o registro com o UID informado\n                │          ╰-------------> adicionar uma nota datada sem o editor: \"<UID> --append-note <texto>\"\n                │          ╰-------------> perguntar antes de salvar o registro editado: \"<UID> --confirm\"\n                ╰------------------------> editar todos os registros de um diário: \"-j\""),
+    ("usage_last", "    last                              edita o registro criado por último"),
+    ("usage_goal", "    goal   <diário> <porcentagem>     define uma meta de porcentagem para o diário, exibida com \"show -m\""),
+    ("usage_color", "    color  <diário> <cor>             define a cor do nome do diário em \"show\", \"none\" remove a cor"),
+    ("usage_alias", "    alias  <diário> <ação> <apelido>  nome curto aceito onde um diário é esperado\n                ╰------------------------> ação pode ser: [add, remove]"),
+    ("usage_merge", "    merge  <origem> <destino>         move os registros de <origem> para <destino> e remove <origem>"),
+    ("usage_merge_logs", "    merge-logs <UID> <UID>            soma o segundo registro ao primeiro, ex. uma sessão registrada duas vezes\n                ╰------------------------> mesclar registros com matéria ou tópico diferentes: \"--force\""),
+    ("usage_rename_subject", "    rename-subject <antigo> <novo>    renomeia uma matéria em todos os registros, ignorando maiúsculas\n                ╰------------------------> renomear um tópico: \"--topic\""),
+    ("usage_purge", "    purge                             apaga todos os diários após confirmar, um backup é mantido"),
+    ("usage_archive", "    archive <UID>                     esconde um registro de show, get, stats e revisões sem apagá-lo, export o mantém\n                │          ╰-------------> trazê-lo de volta: \"unarchive <UID>\"\n                ╰------------------------> mostrar registros arquivados em show ou get: \"--include-archived\""),
+    ("usage_stats", "    stats  <subcomando>               mostra estatísticas dos registros, registros por faixa de porcentagem por padrão\n                │          ╰-------------> agrupar por: \"--by <week|month>\"\n                │          ╰-------------> só um diário, com um relatório detalhado: \"-j <nome>\"\n                │          ╰-------------> porcentagem por tópico de um diário, os piores primeiro: \"-j <nome> --by-topic\"\n                ╰------------------------> registros por período: \"--histogram\""),
+    ("usage_review", "    review                            lista os registros a revisar, mais cedo quanto menor a porcentagem"),
+    ("usage_recent", "    recent [N]                        mostra os N registros mais recentes de todos os diários, 10 por padrão"),
+    ("usage_weak", "    weak   [N]                        mostra os N registros com menor porcentagem de todos os diários, 5 por padrão"),
+    ("usage_plan", "    plan   [N]                        mostra os N tópicos que mais precisam de revisão, pela última porcentagem e há quanto tempo foi, 5 por padrão"),
+    ("usage_template", "    template <subcomando>             personaliza o modelo de nota, `template.txt` no diretório de dados\n                │          ╰-------------> mostra a nota de onde um novo registro começa: \"--show\"\n                ╰------------------------> escrever o modelo padrão: \"--init\""),
+    ("usage_config", "    config <subcomando>               configurações padrão de editor, ordenação, cor, arquivo de dados e arredondamento, `config.toml` no diretório de dados\n                ╰------------------------> mostrar a configuração efetiva: \"--show\""),
+    ("usage_open", "    open   <subcomando>               edita o arquivo de dados no seu editor e o valida depois\n                │          ╰-------------> abrir o link de recurso de um registro no navegador: \"<UID> --resource\"\n                ╰------------------------> abrir o diretório de dados no gerenciador de arquivos: \"--dir\""),
+    ("usage_diff", "    diff   <arquivo.json>             compara os dados com outro arquivo, - só aqui, + só no outro, ~ alterado"),
+    ("usage_export", "    export <subcomando>               grava os diários em um arquivo que pode ser lido como arquivo de dados\n                │          ╰-------------> arquivo de destino: \"--json <caminho>\"\n                ╰------------------------> só um diário: \"-j <nome>\""),
+];
+
+fn lookup(table: Table, key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+fn table_for(lang: &str) -> Table {
+    // Accept locale-like values such as `pt_BR.UTF-8` or `pt-BR`
+    let code = lang
+        .split(['_', '-', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    LANGUAGES
+        .iter()
+        .find(|(name, _)| *name == code)
+        .map(|(_, table)| *table)
+        .unwrap_or(EN)
+}
+
+pub fn text_in(lang: &str, key: &'static str) -> &'static str {
+    lookup(table_for(lang), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key)
+}
+
+pub fn text(key: &'static str) -> &'static str {
+    text_in(&env::var("STU_LANG").unwrap_or_default(), key)
+}
+
+pub fn text_with(key: &'static str, args: &[&dyn Display]) -> String {
+    let mut args = args.iter();
+    let mut result = String::new();
+    let mut parts = text(key).split("{}").peekable();
+
+    while let Some(part) = parts.next() {
+        result.push_str(part);
+        if parts.peek().is_some() {
+            if let Some(arg) = args.next() {
+                result.push_str(&arg.to_string());
+            }
+        }
+    }
+
+    result
+}
+
+pub fn imported_logs(count: usize, journal_name: &str) -> String {
+    text_with("imported_logs", &[&count, &journal_name])
+}
+
+pub fn exported(count: usize, path: &str) -> String {
+    text_with("exported", &[&count, &path])
+}
+
+pub fn wrote_output(path: &str) -> String {
+    text_with("wrote_output", &[&path])
+}
+
+pub fn created_journal() -> String {
+    text("created_journal").to_string()
+}
+
+pub fn added_log(journal_name: &str) -> String {
+    text_with("added_log", &[&journal_name])
+}
+
+pub fn removed_journal(journal_name: &str) -> String {
+    text_with("removed_journal", &[&journal_name])
+}
+
+pub fn pruned_journals(count: usize) -> String {
+    text_with("pruned_journals", &[&count])
+}
+
+pub fn removed_log(uid: &str) -> String {
+    text_with("removed_log", &[&uid])
+}
+
+pub fn edited_log(uid: &str) -> String {
+    text_with("edited_log", &[&uid])
+}
+
+pub fn goal_set(goal: f32, journal_name: &str) -> String {
+    text_with("goal_set", &[&goal, &journal_name])
+}
+
+pub fn merged_journals(count: usize, source: &str, dest: &str) -> String {
+    text_with("merged_journals", &[&count, &source, &dest])
+}
+
+pub fn merged_logs(uid: &str, into_uid: &str) -> String {
+    text_with("merged_logs", &[&uid, &into_uid])
+}
+
+pub fn purged(backup: &str) -> String {
+    text_with("purged", &[&backup])
+}
+
+pub fn renamed(count: usize, old: &str, new: &str) -> String {
+    text_with("renamed", &[&old, &new, &count])
+}
+
+pub fn color_set(color: &str, journal_name: &str) -> String {
+    text_with("color_set", &[&color, &journal_name])
+}
+
+pub fn color_cleared(journal_name: &str) -> String {
+    text_with("color_cleared", &[&journal_name])
+}
+
+pub fn alias_added(alias: &str, journal_name: &str) -> String {
+    text_with("alias_added", &[&alias, &journal_name])
+}
+
+pub fn alias_removed(alias: &str, journal_name: &str) -> String {
+    text_with("alias_removed", &[&alias, &journal_name])
+}
+
+pub fn created_template(path: &Path) -> String {
+    text_with("created_template", &[&path.display()])
+}
+
+pub fn results(count: usize) -> String {
+    match count {
+        1 => text("result_count_one").to_string(),
+        _ => text_with("result_count", &[&count]),
+    }
+}
+
+pub fn days_ago(days: i64) -> String {
+    match days {
+        0 => text("days_ago_today").to_string(),
+        1 => text("days_ago_one").to_string(),
+        _ => text_with("days_ago", &[&days]),
+    }
+}
+
+pub fn edited_journal(count: usize, journal_name: &str) -> String {
+    text_with("edited_journal", &[&count, &journal_name])
+}
@@ -1,294 +1,2152 @@
 use colored::Colorize;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::fs::File;
-use std::io::{Read, Seek, Write};
+use std::hash::{Hash, Hasher};
+use std::io::{IsTerminal, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tabled::{
-    format::Format, locator::ByColumnName, object::Rows, object::*, style::Style, BorderText,
-    Disable, Modify, Table, Tabled, Width,
+    format::Format,
+    locator::ByColumnName,
+    object::{Cell, Rows},
+    peaker::PriorityMax,
+    style::{HorizontalLine, Line, Style},
+    BorderText, Disable, Modify, Table, Tabled, Width,
 };
 use tempfile::Builder;
 
-#[derive(Tabled, Serialize, Deserialize, Debug, Clone)]
+use cli::ShowOptions;
+use config::TagRules;
+use messages::{text, text_with};
+
+// Set by `trust_stored_percentage` in the config, see `stored_or_computed_percentage`
+static TRUST_STORED_PERCENTAGE: OnceLock<bool> = OnceLock::new();
+
+pub fn set_trust_stored_percentage(trust: bool) {
+    let _ = TRUST_STORED_PERCENTAGE.set(trust);
+}
+
+// Why a command line can't be run, already in the user's language. Parsing
+// only builds it so it can be checked in tests, `cli::run` prints it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StuError {
+    pub message: String,
+    // The usage goes above the message, for when no subcommand was given
+    pub usage: bool,
+}
+impl StuError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            usage: false,
+        }
+    }
+
+    pub fn with_usage(self) -> Self {
+        Self {
+            usage: true,
+            ..self
+        }
+    }
+}
+impl std::fmt::Display for StuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Log {
-    #[tabled(rename = "Subject")]
     pub subject: String,
+    // Files before schema 3 kept a single `topic` string, commas in it are
+    // read as separate topics
+    #[serde(alias = "topic", deserialize_with = "topic_list")]
+    pub topics: Vec<String>,
+    pub date: String,
+    pub uid: String,
+    #[serde(default, deserialize_with = "lenient_total_questions")]
+    pub total_questions: usize,
+    #[serde(default, deserialize_with = "lenient_right_answers")]
+    pub right_answers: usize,
+    // Worked out again from the counts whenever a file is read
+    #[serde(default, deserialize_with = "lenient_percentage")]
+    pub percentage: f32,
 
-    #[tabled(rename = "Topic")]
-    pub topic: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
 
-    #[tabled(rename = "Date")]
-    pub date: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 
-    #[tabled(rename = "UID")]
-    pub uid: String,
+    // Unix timestamp, logs saved before it existed get one from their date
+    #[serde(default)]
+    pub created_at: u64,
 
-    #[tabled(rename = "Questions")]
-    pub total_questions: usize,
+    // Link to what was studied, opened with `stu open <uid> --resource`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
 
-    #[tabled(rename = "Right answers")]
-    pub right_answers: usize,
+    // Hidden from `show`, `get` and the other views but kept, and exported
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+}
 
-    #[tabled(rename = "Percentage")]
-    pub percentage: f32,
+// Hand edited files sometimes hold counts as strings or null, those are read
+// as the number they spell or 0. Each coerced count is noted for the warning
+// printed once the log is read, see `StoredJournal`
+fn lenient_total_questions<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    lenient_count(deserializer, "total_questions")
+}
+
+fn lenient_right_answers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    lenient_count(deserializer, "right_answers")
+}
+
+fn lenient_count<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    field: &'static str,
+) -> Result<usize, D::Error> {
+    let value = LooseValue::deserialize(deserializer)?;
+    let count = value.count().unwrap_or(0);
+    if !matches!(value, LooseValue::Unsigned(_)) {
+        COERCED_COUNTS.with(|coerced| coerced.borrow_mut().push((field, value, count)));
+    }
+    Ok(count)
+}
+
+fn lenient_percentage<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    Ok(match LooseValue::deserialize(deserializer)? {
+        LooseValue::Unsigned(x) => x as f32,
+        LooseValue::Float(x) => x as f32,
+        _ => 0.0,
+    })
+}
+
+thread_local! {
+    // Counts `lenient_count` had to coerce in the log being read
+    static COERCED_COUNTS: std::cell::RefCell<Vec<(&'static str, LooseValue, usize)>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
+
+fn topic_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Topics {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match Topics::deserialize(deserializer)? {
+        Topics::One(topic) => utils::parse_topics(&topic),
+        Topics::Many(topics) => topics.iter().flat_map(|x| utils::parse_topics(x)).collect(),
+    })
+}
+
+// A scalar read without building a `Value`, for fields hand edited files get
+// wrong. Arrays and objects are skipped and only kept as a placeholder
+#[derive(Debug, Clone, PartialEq)]
+pub enum LooseValue {
+    Unsigned(u64),
+    Float(f64),
+    Text(String),
+    Other(&'static str),
+}
+
+impl LooseValue {
+    pub fn count(&self) -> Option<usize> {
+        match self {
+            LooseValue::Unsigned(x) => Some(*x as usize),
+            LooseValue::Float(x) if *x >= 0.0 && x.fract() == 0.0 => Some(*x as usize),
+            LooseValue::Text(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LooseValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LooseValue::Unsigned(x) => write!(f, "{x}"),
+            LooseValue::Float(x) => write!(f, "{x}"),
+            LooseValue::Text(text) => write!(f, "{text:?}"),
+            LooseValue::Other(other) => write!(f, "{other}"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for LooseValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LooseVisitor;
+
+        impl<'de> de::Visitor<'de> for LooseVisitor {
+            type Value = LooseValue;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("any value")
+            }
+
+            fn visit_u64<E: de::Error>(self, x: u64) -> Result<LooseValue, E> {
+                Ok(LooseValue::Unsigned(x))
+            }
+
+            fn visit_i64<E: de::Error>(self, x: i64) -> Result<LooseValue, E> {
+                Ok(LooseValue::Float(x as f64))
+            }
+
+            fn visit_f64<E: de::Error>(self, x: f64) -> Result<LooseValue, E> {
+                Ok(LooseValue::Float(x))
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<LooseValue, E> {
+                Ok(LooseValue::Text(text.to_string()))
+            }
+
+            fn visit_bool<E: de::Error>(self, x: bool) -> Result<LooseValue, E> {
+                Ok(LooseValue::Other(if x { "true" } else { "false" }))
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<LooseValue, E> {
+                Ok(LooseValue::Other("null"))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<LooseValue, A::Error> {
+                while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+                Ok(LooseValue::Other("[...]"))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<LooseValue, A::Error> {
+                while map
+                    .next_entry::<de::IgnoredAny, de::IgnoredAny>()?
+                    .is_some()
+                {}
+                Ok(LooseValue::Other("{...}"))
+            }
+        }
+
+        deserializer.deserialize_any(LooseVisitor)
+    }
+}
+
+// Headers are looked up at runtime so they follow `STU_LANG`, which the
+// derive macro can't do.
+impl Tabled for Log {
+    const LENGTH: usize = 7;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            Cow::Borrowed(self.subject.as_str()),
+            Cow::Owned(self.topic_label()),
+            Cow::Owned(utils::display_date(&self.date)),
+            Cow::Borrowed(self.uid.as_str()),
+            Cow::Owned(self.total_questions.to_string()),
+            Cow::Owned(self.right_answers.to_string()),
+            Cow::Owned(utils::format_percentage(
+                self.percentage,
+                utils::PERCENTAGE_PRECISION,
+            )),
+        ]
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        [
+            "header_subject",
+            "header_topic",
+            "header_date",
+            "header_uid",
+            "header_questions",
+            "header_right_answers",
+            "header_percentage",
+        ]
+        .into_iter()
+        .map(|key| Cow::Borrowed(text(key)))
+        .collect()
+    }
+}
+impl Default for Log {
+    fn default() -> Self {
+        Self::new()
+    }
 }
+// What a log's date is left as when the system date couldn't be read
+pub const UNKNOWN_DATE: &str = "unknown";
+
 impl Log {
     pub fn new() -> Self {
-        let random_uid: String = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .subsec_nanos()
-            .to_string();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let random_uid: String = now.subsec_nanos().to_string();
         Self {
             subject: "unknown".to_string(),
-            topic: "unknown".to_string(),
-            date: "unknown".to_string(),
+            topics: vec!["unknown".to_string()],
+            date: UNKNOWN_DATE.to_string(),
             uid: random_uid,
             total_questions: 0,
             right_answers: 0,
             percentage: 0.0,
+            notes: String::new(),
+            tags: Vec::new(),
+            created_at: now.as_secs(),
+            resource: None,
+            archived: false,
+        }
+    }
+
+    // Days since 1970-01-01 the log was taken, from its date when it can be read
+    pub fn day(&self) -> Option<i64> {
+        match utils::parse_date(&self.date) {
+            Some((year, month, day)) => Some(utils::days_from_civil(year, month, day)),
+            None if self.created_at > 0 => Some((self.created_at / 86400) as i64),
+            None => None,
+        }
+    }
+
+    // Every topic in one cell, `limits, derivatives`
+    pub fn topic_label(&self) -> String {
+        self.topics.join(", ")
+    }
+
+    // Whether any of the topics is `topic`, ignoring case
+    pub fn has_topic(&self, topic: &str) -> bool {
+        self.topics
+            .iter()
+            .any(|x| x.to_lowercase() == topic.to_lowercase())
+    }
+
+    pub fn append_note(&mut self, date: &str, note: &str) {
+        if !self.notes.is_empty() {
+            self.notes.push('\n');
         }
+        self.notes.push_str(&format!("[{date}] {note}"));
     }
 }
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "StoredJournal")]
 pub struct Journal {
     pub name: String,
     pub logs: Vec<Log>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal_percentage: Option<f32>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
 }
 impl Journal {
     pub fn new(name: &str) -> Self {
         Self {
             name: name.to_string(),
             logs: Vec::new(),
+            goal_percentage: None,
+            color: None,
+            aliases: Vec::new(),
         }
     }
     pub fn add_log(&mut self, log: Log) {
         self.logs.push(log);
     }
+
+    // Whether `name` is the journal name or one of its aliases
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|alias| alias == name)
+    }
+
+    // BorderText counts escape codes as width, so the name is painted after rendering
+    fn color_header(&self, rendered: String, header: &str) -> String {
+        let color = self.color.as_deref().and_then(utils::parse_color);
+        match (color, rendered.strip_prefix(header)) {
+            (Some(color), Some(rest)) => format!("{}{rest}", header.color(color)),
+            _ => rendered,
+        }
+    }
+}
+// Keeps the parsed journals around so views that redraw, like a live view,
+// only parse `data.json` again after it was written to
+pub struct JournalStore {
+    pub path: PathBuf,
+    pub journals: Vec<Journal>,
+    modified: Option<SystemTime>,
+}
+impl JournalStore {
+    // Files from an older schema are upgraded and written back right away
+    pub fn load(filepath: impl AsRef<Path>) -> Result<Self, ()> {
+        Self::open(filepath.as_ref(), true)
+    }
+
+    // For commands that only move, rename or count logs and never show a
+    // percentage, the stored percentages are kept as they are instead of
+    // being worked out again for every log
+    pub fn load_without_percentages(filepath: impl AsRef<Path>) -> Result<Self, ()> {
+        Self::open(filepath.as_ref(), false)
+    }
+
+    fn open(filepath: &Path, recompute: bool) -> Result<Self, ()> {
+        let (schema, journals) = match read_data_file(filepath, recompute) {
+            Ok(read) => read,
+            Err(()) => {
+                recover_data(filepath)?;
+                read_data_file(filepath, recompute)?
+            }
+        };
+        let mut store = Self {
+            path: filepath.to_path_buf(),
+            modified: modified_time(filepath),
+            journals,
+        };
+
+        if schema < SCHEMA_VERSION {
+            // Logs that couldn't be read are left out of the upgraded file,
+            // so the original stays around
+            let backup = backup_data(filepath, &format!("schema{schema}"))?;
+            store.save()?;
+            eprintln!(
+                "{}: {}",
+                text("warning").yellow(),
+                text_with(
+                    "migrated_data",
+                    &[
+                        &filepath.display(),
+                        &schema,
+                        &SCHEMA_VERSION,
+                        &backup.display()
+                    ]
+                )
+            );
+        }
+
+        Ok(store)
+    }
+
+    // Returns whether the journals were read again
+    pub fn reload_if_changed(&mut self) -> Result<bool, ()> {
+        let modified = modified_time(&self.path);
+        if modified.is_some() && modified == self.modified {
+            return Ok(false);
+        }
+
+        self.journals = read_journals(&self.path)?;
+        self.modified = modified;
+        Ok(true)
+    }
+
+    // Where drafts, the note template and tag rules go with the data file
+    pub fn dir(&self) -> &Path {
+        utils::data_dir_of(&self.path)
+    }
+
+    pub fn save(&mut self) -> Result<(), ()> {
+        write_journals(&self.journals, &self.path)?;
+        self.modified = modified_time(&self.path);
+        Ok(())
+    }
+}
+
+fn modified_time(filepath: &Path) -> Option<SystemTime> {
+    fs::metadata(filepath).and_then(|x| x.modified()).ok()
 }
-pub fn get_journals(filepath: &str, journals: &mut Vec<Journal>) -> Result<(), ()> {
-    let json_str: &str = &fs::read_to_string(filepath)
-        .map_err(|err| eprintln!("{}: Could not read json filepath {err}", "ERROR".red()))?;
 
-    let objects: Value = serde_json::from_str::<Value>(json_str).map_err(|err| {
+// Copies the data file to `{filepath}.{label}.bak` before it is overwritten,
+// returning the copy's path
+pub fn backup_data(filepath: impl AsRef<Path>, label: &str) -> Result<PathBuf, ()> {
+    let filepath = filepath.as_ref();
+    let mut backup = filepath.as_os_str().to_owned();
+    backup.push(format!(".{label}.bak"));
+    let backup = PathBuf::from(backup);
+
+    fs::copy(filepath, &backup).map_err(|err| {
         eprintln!(
-            "{}: Could not create json object from string: {err}",
-            "ERROR".red()
-        )
+            "{}: {}",
+            text("error").red(),
+            text_with("create_file_failed", &[&err])
+        );
     })?;
 
-    for journal_objs in objects.as_array() {
-        for journal_value in journal_objs {
-            let name = journal_value["name"].as_str().ok_or_else(|| {
-                eprintln!(
-                    "{}: Value `Name` not found in {filepath} at `{value}`",
-                    "ERROR".red(),
-                    value = "Journals"
-                );
-            })?;
-            let mut journal: Journal = Journal::new(name);
-            if journal_value["logs"].is_null() {
-                eprintln!(
-                    "{}: Value `logs` not found in {filepath} at `{value}` journal",
-                    "ERROR".red(),
-                    value = journal_value["name"].as_str().unwrap()
-                );
-                return Err(());
+    Ok(backup)
+}
+
+// The newest `backup_data` copy of the data file that is still valid JSON
+pub fn newest_backup(filepath: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = filepath.as_ref();
+    let prefix = format!("{}.", path.file_name()?.to_string_lossy());
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut backups: Vec<(SystemTime, PathBuf)> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with(&prefix) && name.ends_with(".bak")
+        })
+        .filter_map(|entry| {
+            let modified = entry.metadata().and_then(|x| x.modified()).ok()?;
+            Some((modified, entry.path()))
+        })
+        .collect();
+    backups.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    backups
+        .into_iter()
+        .map(|(_, backup)| backup)
+        .find(|backup| {
+            fs::read_to_string(backup).is_ok_and(|content| {
+                serde_json::from_str::<serde::de::IgnoredAny>(&content).is_ok()
+            })
+        })
+}
+
+// Copies `backup` over the data file, the damaged file is kept next to it as
+// `{filepath}.damaged-{unix secs}.bak`, whose path is returned
+pub fn restore_backup(filepath: impl AsRef<Path>, backup: impl AsRef<Path>) -> Result<PathBuf, ()> {
+    let filepath = filepath.as_ref();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_secs())
+        .unwrap_or(0);
+    let damaged = backup_data(filepath, &format!("damaged-{now}"))?;
+    fs::copy(backup, filepath).map_err(|err| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("create_file_failed", &[&err])
+        );
+    })?;
+
+    Ok(damaged)
+}
+
+// A data file that isn't JSON anymore, e.g. cut short by a crash while it was
+// written, would fail every command. The newest readable backup is offered in
+// its place, only when someone is there to answer
+fn recover_data(filepath: &Path) -> Result<(), ()> {
+    let damaged = fs::read_to_string(filepath)
+        .is_ok_and(|content| serde_json::from_str::<serde::de::IgnoredAny>(&content).is_err());
+    if !damaged {
+        return Err(());
+    }
+    let backup = newest_backup(filepath).ok_or(())?;
+
+    if !std::io::stdin().is_terminal() {
+        eprintln!(
+            "{}: {}",
+            text("warning").yellow(),
+            text_with(
+                "restore_backup_hint",
+                &[&filepath.display(), &backup.display()]
+            )
+        );
+        return Err(());
+    }
+
+    eprintln!(
+        "{}",
+        text_with("data_damaged", &[&filepath.display(), &backup.display()])
+    );
+    if !utils::confirm(text("restore_backup_prompt")) {
+        eprintln!(
+            "{}",
+            text_with(
+                "restore_backup_hint",
+                &[&filepath.display(), &backup.display()]
+            )
+            .dimmed()
+        );
+        return Err(());
+    }
+
+    let damaged = restore_backup(filepath, &backup)?;
+    eprintln!(
+        "{}: {}",
+        text("warning").yellow(),
+        text_with(
+            "restored_backup",
+            &[&filepath.display(), &backup.display(), &damaged.display()]
+        )
+    );
+    Ok(())
+}
+
+pub fn get_journals(filepath: impl AsRef<Path>, journals: &mut Vec<Journal>) -> Result<(), ()> {
+    journals.extend(JournalStore::load(filepath)?.journals);
+    Ok(())
+}
+
+// Version of the data file layout, bumped whenever reading an older file needs
+// more than serde defaults. 0 is the bare array of journals from before the
+// `{ "schema": .., "journals": [..] }` wrapper, 1 stored dates as `MM/DD/YYYY`
+// instead of `YYYY-MM-DD` and 2 kept a single `topic` string per log
+pub const SCHEMA_VERSION: u64 = 3;
+
+#[derive(Serialize)]
+struct DataFile<'a> {
+    schema: u64,
+    journals: &'a [Journal],
+}
+
+// Reads a data file without upgrading it on disk, for files that aren't ours
+// like the one given to `diff`
+pub fn read_journals(filepath: impl AsRef<Path>) -> Result<Vec<Journal>, ()> {
+    Ok(read_data_file(filepath.as_ref(), true)?.1)
+}
+
+// A wrapped data file, read in a single pass. The journals of a file from a
+// newer stu are skipped unread, so it is refused instead of failing on a
+// layout it may not share. stu always writes `schema` before `journals`
+struct WrappedFile {
+    schema: Option<u64>,
+    journals: Option<Vec<Journal>>,
+}
+
+impl<'de> Deserialize<'de> for WrappedFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FileVisitor;
+
+        impl<'de> de::Visitor<'de> for FileVisitor {
+            type Value = WrappedFile;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a stu data file")
             }
 
-            for log_objs in journal_value["logs"].as_array() {
-                for mut log_value in log_objs.clone() {
-                    let percentage = utils::get_percentage(
-                        *&log_value["right_answers"].as_u64().unwrap_or(0) as f32,
-                        *&log_value["total_questions"].as_u64().unwrap_or(0) as f32,
-                    );
-                    log_value["percentage"] = serde_json::to_value(&percentage).unwrap();
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<WrappedFile, A::Error> {
+                let mut file = WrappedFile {
+                    schema: None,
+                    journals: None,
+                };
+                while let Some(key) = map.next_key::<Cow<'de, str>>()? {
+                    match key.as_ref() {
+                        "schema" => file.schema = Some(map.next_value()?),
+                        "journals" if file.schema.unwrap_or(0) <= SCHEMA_VERSION => {
+                            file.journals = Some(map.next_value()?)
+                        }
+                        _ => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(file)
+            }
+        }
+
+        deserializer.deserialize_map(FileVisitor)
+    }
+}
+
+// A journal as stored. Its logs are kept as their raw text and read one by
+// one, so one unreadable log is left out instead of failing the whole file
+#[derive(Deserialize)]
+struct StoredJournal {
+    name: String,
+    logs: Vec<Box<RawValue>>,
+    #[serde(default)]
+    goal_percentage: Option<f32>,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+}
 
-                    let log: Log = serde_json::from_str(&log_value.to_string()).map_err(|err| {
+impl From<StoredJournal> for Journal {
+    fn from(stored: StoredJournal) -> Self {
+        let mut journal = Journal::new(&stored.name);
+        journal.goal_percentage = stored.goal_percentage;
+        journal.color = stored.color;
+        journal.aliases = stored.aliases;
+
+        for raw in stored.logs {
+            // Logs read anywhere else leave their coerced counts behind too
+            COERCED_COUNTS.with(|coerced| coerced.borrow_mut().clear());
+            let log = serde_json::from_str::<Log>(raw.get());
+            let coerced = COERCED_COUNTS.with(|coerced| coerced.take());
+
+            match log {
+                Ok(log) => {
+                    for (field, value, count) in coerced {
                         eprintln!(
-                            "{}: Could not deserialize json into log \
-                             struct: {err}",
-                            "ERROR".red()
+                            "{}: {}",
+                            text("warning").yellow(),
+                            text_with(
+                                "coerced_log_field",
+                                &[&field, &log.uid, &journal.name, &value, &count]
+                            )
                         );
-                    })?;
-                    journal.add_log(log.clone());
+                    }
+                    journal.logs.push(log);
+                }
+                Err(err) => {
+                    #[derive(Deserialize)]
+                    struct LogUid {
+                        uid: String,
+                    }
+                    let uid = serde_json::from_str::<LogUid>(raw.get())
+                        .map_or_else(|_| "?".to_string(), |x| x.uid);
+                    eprintln!(
+                        "{}: {}",
+                        text("warning").yellow(),
+                        text_with("skipped_log", &[&uid, &journal.name, &err])
+                    );
                 }
             }
-            journals.push(journal);
         }
+
+        journal
     }
+}
 
-    Ok(())
+// The schema the file was written with and its journals. Without `recompute`
+// logs keep the percentage they were stored with, unless the file is about to
+// be upgraded and written back
+fn read_data_file(filepath: &Path, recompute: bool) -> Result<(u64, Vec<Journal>), ()> {
+    let json_str: &str = &fs::read_to_string(filepath).map_err(|err| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("read_json_failed", &[&err])
+        )
+    })?;
+
+    let parse_failed = |err: serde_json::Error| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("parse_json_failed", &[&err])
+        )
+    };
+    let invalid_data_file = || {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("invalid_data_file", &[&filepath.display()])
+        )
+    };
+    let (schema, mut journals) = match json_str.trim_start().chars().next() {
+        Some('[') => (
+            0,
+            serde_json::from_str::<Vec<Journal>>(json_str).map_err(parse_failed)?,
+        ),
+        Some('{') => match serde_json::from_str::<WrappedFile>(json_str).map_err(parse_failed)? {
+            WrappedFile {
+                schema: Some(schema),
+                ..
+            } if schema > SCHEMA_VERSION => {
+                eprintln!(
+                    "{}: {}",
+                    text("error").red(),
+                    text_with("newer_schema", &[&filepath.display(), &schema])
+                );
+                return Err(());
+            }
+            WrappedFile {
+                schema: Some(schema),
+                journals: Some(journals),
+            } => (schema, journals),
+            _ => {
+                invalid_data_file();
+                return Err(());
+            }
+        },
+        _ => {
+            serde_json::from_str::<serde::de::IgnoredAny>(json_str).map_err(parse_failed)?;
+            invalid_data_file();
+            return Err(());
+        }
+    };
+
+    for log in journals
+        .iter_mut()
+        .flat_map(|journal| journal.logs.iter_mut())
+    {
+        if recompute || schema < SCHEMA_VERSION {
+            log.percentage = stored_or_computed_percentage(
+                log,
+                TRUST_STORED_PERCENTAGE.get().copied().unwrap_or_default(),
+            );
+        }
+        if log.created_at == 0 {
+            log.created_at = log.day().unwrap_or(0).max(0) as u64 * 86400;
+        }
+        if schema < 2 {
+            if let Some((year, month, day)) = utils::parse_date(&log.date) {
+                log.date = utils::canonical_date(year, month, day);
+            }
+        }
+    }
+
+    for name in duplicate_journal_names(&journals) {
+        let count = journals.iter().filter(|x| x.name == name).count();
+        eprintln!(
+            "{}: {}",
+            text("warning").yellow(),
+            text_with("duplicate_journal", &[&count, &name])
+        );
+    }
+
+    Ok((schema, journals))
 }
 
-pub fn show_metrics(journals: &Vec<Journal>) {
-    for journal in journals {
-        let mut sum_questions = 0;
-        let mut sum_answers = 0;
+// The percentage a log is read with. By default it's always worked out again
+// from the counts, so a hand edited count can't leave a stale percentage behind.
+// Trusting the stored one keeps hand set scores like partial credit, at the
+// cost of those logs no longer following their counts or the rounding mode.
+// A stored 0 is taken as absent either way
+pub fn stored_or_computed_percentage(log: &Log, trust_stored: bool) -> f32 {
+    if trust_stored && log.percentage.is_finite() && log.percentage != 0.0 {
+        return log.percentage;
+    }
 
-        for log in journal.logs.iter() {
-            sum_questions += log.total_questions;
-            sum_answers += log.right_answers;
+    utils::get_percentage(log.right_answers as f32, log.total_questions as f32)
+}
+
+// Leaves archived logs out of what is about to be shown, unless
+// `--include-archived` asks for them. Never call it before saving
+pub fn hide_archived(journals: &mut [Journal], options: &ShowOptions) {
+    if options.include_archived {
+        return;
+    }
+
+    for journal in journals.iter_mut() {
+        journal.logs.retain(|log| !log.archived);
+    }
+}
+
+// Whether a new journal or alias called `name` would make it point at two
+// journals
+pub fn name_taken(journals: &[Journal], name: &str) -> bool {
+    journals.iter().any(|journal| journal.is_named(name))
+}
+
+// Names used by more than one journal, e.g. after hand editing `data.json`
+pub fn duplicate_journal_names(journals: &[Journal]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (index, journal) in journals.iter().enumerate() {
+        let repeated = journals[index + 1..].iter().any(|x| x.name == journal.name);
+        if repeated && !names.contains(&journal.name) {
+            names.push(journal.name.clone());
         }
-        let mut sum_percentage = if sum_questions == 0 && sum_answers == 0 {
-            "0.0".to_string()
-        } else {
-            utils::get_percentage(sum_answers as f32, sum_questions as f32).to_string()
-        };
+    }
+
+    names
+}
+
+// Summed questions and right answers of a journal with its overall percentage
+fn journal_totals(journal: &Journal) -> (usize, usize, f32) {
+    let mut sum_questions = 0;
+    let mut sum_answers = 0;
+
+    for log in journal.logs.iter() {
+        sum_questions += log.total_questions;
+        sum_answers += log.right_answers;
+    }
+    let percentage = utils::get_percentage(sum_answers as f32, sum_questions as f32);
+
+    (sum_questions, sum_answers, percentage)
+}
 
-        sum_percentage.push('%');
+// How a journal's percentage is worked out from its logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mean {
+    // Summed right answers over summed questions, so bigger sessions weigh more
+    #[default]
+    Weighted,
+    // The mean of the log percentages, every session weighs the same
+    Simple,
+}
+impl Mean {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "weighted" => Some(Self::Weighted),
+            "simple" => Some(Self::Simple),
+            _ => None,
+        }
+    }
+}
+
+// 0 for a journal without logs
+pub fn simple_mean(journal: &Journal) -> f32 {
+    if journal.logs.is_empty() {
+        return 0.0;
+    }
+    journal.logs.iter().map(|log| log.percentage).sum::<f32>() / journal.logs.len() as f32
+}
+
+// Totals of a journal with its percentage worked out the `mean` way
+fn journal_metrics(journal: &Journal, mean: Mean) -> (usize, usize, f32) {
+    let (sum_questions, sum_answers, percentage) = journal_totals(journal);
+    match mean {
+        Mean::Weighted => (sum_questions, sum_answers, percentage),
+        Mean::Simple => (sum_questions, sum_answers, simple_mean(journal)),
+    }
+}
 
-        let sum_questions: &str = &sum_questions.to_string();
-        let sum_answers: &str = &sum_answers.to_string();
+pub fn show_metrics(
+    out: &mut impl Write,
+    journals: &[Journal],
+    style: TableStyle,
+    mean: Mean,
+) -> std::io::Result<()> {
+    for journal in journals {
+        let (sum_questions, sum_answers, percentage) = journal_metrics(journal, mean);
+        let sum_percentage = utils::format_percentage(percentage, utils::PERCENTAGE_PRECISION);
 
         let mut builder = tabled::builder::Builder::default();
-        builder.set_columns(["", "Total"]);
-        builder.add_record(["Questions", sum_questions]);
-        builder.add_record(["Answers", sum_answers]);
-        builder.add_record(["Percentage", &sum_percentage]);
+        builder.set_columns(["", text("header_total")]);
+        builder.add_record([
+            text("header_questions").to_string(),
+            sum_questions.to_string(),
+        ]);
+        builder.add_record([text("header_answers").to_string(), sum_answers.to_string()]);
+        builder.add_record([
+            text("header_percentage").to_string(),
+            sum_percentage.clone(),
+        ]);
         let mut builder = builder.index();
         builder.hide_index();
 
         let mut metrics_table = builder.build();
-        metrics_table
-            .with(Width::list([10, 7]))
-            .with(Style::rounded())
-            .with(BorderText::new(0, format!("{}", journal.name)));
+        metrics_table.with(Width::list([10, 7]));
+        let metrics_table = render_table(metrics_table, &journal.name, style, TableFormat::Wide);
+
+        writeln!(
+            out,
+            "{}",
+            journal.color_header(metrics_table, &journal.name)
+        )?;
 
-        println!("{metrics}", metrics = metrics_table.to_string());
+        if let Some(goal) = journal.goal_percentage {
+            writeln!(
+                out,
+                "{}: {goal} {bar} {sum_percentage}",
+                text("header_goal"),
+                goal = utils::format_percentage(goal, utils::PERCENTAGE_PRECISION),
+                bar = utils::progress_bar(percentage, goal, 20)
+            )?;
+        }
     }
+
+    Ok(())
 }
 
-pub fn show_journals(journals: &mut Vec<Journal>) {
-    for journal in journals.iter_mut() {
-        unsafe {
-            if crate::SORT {
+// One row of totals per journal, for when a table per journal is too much
+pub fn show_metrics_compact(
+    out: &mut impl Write,
+    journals: &[Journal],
+    style: TableStyle,
+    mean: Mean,
+) -> std::io::Result<()> {
+    let mut builder = tabled::builder::Builder::default();
+    builder.set_columns([
+        text("header_journal"),
+        text("header_questions"),
+        text("header_answers"),
+        text("header_percentage"),
+    ]);
+    for journal in journals {
+        let (sum_questions, sum_answers, percentage) = journal_metrics(journal, mean);
+        builder.add_record([
+            journal.name.clone(),
+            sum_questions.to_string(),
+            sum_answers.to_string(),
+            utils::format_percentage(percentage, utils::PERCENTAGE_PRECISION),
+        ]);
+    }
+
+    writeln!(
+        out,
+        "{}",
+        render_table(builder.build(), "", style, TableFormat::Compact)
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Week,
+    Month,
+}
+impl Granularity {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "week" => Some(Self::Week),
+            "month" => Some(Self::Month),
+            _ => None,
+        }
+    }
+}
+
+// Counts logs per period, keyed by the period start so the map is in
+// chronological order. Logs with unknown dates are ignored.
+pub fn histogram(journals: &[Journal], granularity: Granularity) -> BTreeMap<String, usize> {
+    let mut buckets: BTreeMap<String, usize> = BTreeMap::new();
+
+    for log in journals.iter().flat_map(|journal| journal.logs.iter()) {
+        let days = match log.day() {
+            Some(days) => days,
+            None => continue,
+        };
+
+        let period = match granularity {
+            Granularity::Week => {
+                let (year, month, day) = utils::civil_from_days(utils::week_start(days));
+                format!("{year:04}-{month:02}-{day:02}")
+            }
+            Granularity::Month => {
+                let (year, month, _) = utils::civil_from_days(days);
+                format!("{year:04}-{month:02}")
+            }
+        };
+
+        *buckets.entry(period).or_insert(0) += 1;
+    }
+
+    buckets
+}
+
+pub fn show_histogram(buckets: &BTreeMap<String, usize>) {
+    let label_width = buckets.keys().map(|x| x.len()).max().unwrap_or(0);
+
+    for (period, count) in buckets.iter() {
+        println!(
+            "{period:>label_width$} │ {bar} {count}",
+            bar = "█".repeat(*count).green()
+        );
+    }
+}
+
+// Logs per `utils::PERCENTAGE_BANDS` band
+pub fn percentage_bands(journals: &[Journal]) -> [usize; 4] {
+    let mut bands = [0; 4];
+
+    for log in journals.iter().flat_map(|journal| journal.logs.iter()) {
+        bands[utils::percentage_band(log.percentage)] += 1;
+    }
+
+    bands
+}
+
+pub fn show_bands(bands: &[usize; 4]) {
+    let total: usize = bands.iter().sum();
+
+    let mut builder = tabled::builder::Builder::default();
+    builder.set_columns([text("header_band"), text("header_logs"), ""]);
+    for (label, count) in utils::PERCENTAGE_BANDS.iter().zip(bands) {
+        let share = utils::get_percentage(*count as f32, total as f32);
+        builder.add_record([
+            label.to_string(),
+            count.to_string(),
+            format!(
+                "{} {}",
+                utils::progress_bar(share, 100.0, 20),
+                utils::format_percentage(share, utils::PERCENTAGE_PRECISION)
+            ),
+        ]);
+    }
+
+    println!("{}", builder.build().with(Style::rounded()));
+}
+
+// Totals of the sessions on one topic of a journal, see `stats_by_topic`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicStats {
+    pub topic: String,
+    pub logs: usize,
+    pub total_questions: usize,
+    pub right_answers: usize,
+    pub percentage: f32,
+}
+
+// The logs of `journal` that have questions, summed per topic, worst first.
+// Topics are matched ignoring case and keep the first spelling seen, and a log
+// covering several topics counts toward each of them
+pub fn stats_by_topic(journal: &Journal) -> Vec<TopicStats> {
+    let mut stats: Vec<TopicStats> = Vec::new();
+
+    for log in journal.logs.iter().filter(|log| log.total_questions > 0) {
+        for topic in log.topics.iter() {
+            let index = match stats
+                .iter()
+                .position(|x| x.topic.to_lowercase() == topic.to_lowercase())
+            {
+                Some(index) => index,
+                None => {
+                    stats.push(TopicStats {
+                        topic: topic.clone(),
+                        logs: 0,
+                        total_questions: 0,
+                        right_answers: 0,
+                        percentage: 0.0,
+                    });
+                    stats.len() - 1
+                }
+            };
+            stats[index].logs += 1;
+            stats[index].total_questions += log.total_questions;
+            stats[index].right_answers += log.right_answers;
+        }
+    }
+
+    for topic in stats.iter_mut() {
+        topic.percentage =
+            utils::get_percentage(topic.right_answers as f32, topic.total_questions as f32);
+    }
+    // Stable, so equally weak topics stay in the order they were first studied
+    stats.sort_by(|a, b| a.percentage.total_cmp(&b.percentage));
+    stats
+}
+
+pub fn show_topic_stats(stats: &[TopicStats]) {
+    if stats.is_empty() {
+        println!("{}", text("no_logs_yet").dimmed());
+        return;
+    }
+
+    let mut builder = tabled::builder::Builder::default();
+    builder.set_columns([
+        text("header_topic"),
+        text("header_logs"),
+        text("header_questions"),
+        text("header_right_answers"),
+        text("header_percentage"),
+    ]);
+    for topic in stats {
+        builder.add_record([
+            topic.topic.clone(),
+            topic.logs.to_string(),
+            topic.total_questions.to_string(),
+            topic.right_answers.to_string(),
+            utils::format_percentage(topic.percentage, utils::PERCENTAGE_PRECISION),
+        ]);
+    }
+
+    println!("{}", builder.build().with(Style::rounded()));
+}
+
+// Everything `stats -j` knows about one journal: its totals and goal, the mean
+// of its log percentages, its best and worst logs and the percentage bands
+pub fn show_journal_report(journal: &Journal) {
+    let _ = show_metrics(
+        &mut std::io::stdout().lock(),
+        std::slice::from_ref(journal),
+        TableStyle::Rounded,
+        Mean::Weighted,
+    );
+
+    if journal.logs.is_empty() {
+        println!("{}", text("no_logs_yet").dimmed());
+        return;
+    }
+
+    println!(
+        "{}: {}",
+        text("report_mean"),
+        utils::format_percentage(simple_mean(journal), utils::PERCENTAGE_PRECISION)
+    );
+
+    // The first of equally good logs wins, so ties favor older logs
+    let best = journal.logs.iter().reduce(|best, log| {
+        if log.percentage > best.percentage {
+            log
+        } else {
+            best
+        }
+    });
+    let worst = journal.logs.iter().reduce(|worst, log| {
+        if log.percentage < worst.percentage {
+            log
+        } else {
+            worst
+        }
+    });
+    for (key, log) in [("report_best", best), ("report_worst", worst)] {
+        if let Some(log) = log {
+            println!(
+                "{}: {} / {} {} {}",
+                text(key),
+                log.subject,
+                log.topic_label(),
+                utils::format_percentage(log.percentage, utils::PERCENTAGE_PRECISION),
+                format!("[{}]", log.uid).dimmed()
+            );
+        }
+    }
+    println!();
+
+    show_bands(&percentage_bands(std::slice::from_ref(journal)));
+}
+
+// Logs whose review date (log date plus an interval based on its
+// percentage) is today or earlier, most overdue first.
+pub fn due_logs(journals: &[Journal], today: i64) -> Vec<Log> {
+    let mut due: Vec<(i64, Log)> = Vec::new();
+
+    for log in journals.iter().flat_map(|journal| journal.logs.iter()) {
+        let days = match log.day() {
+            Some(days) => days,
+            None => continue,
+        };
+
+        let review_day = days + utils::review_interval_days(log.percentage);
+        if review_day <= today {
+            due.push((today - review_day, log.clone()));
+        }
+    }
+
+    // Most overdue first, same-day logs in the order they were taken
+    due.sort_by_key(|(overdue, log)| (std::cmp::Reverse(*overdue), log.created_at));
+    due.into_iter().map(|(_, log)| log).collect()
+}
+
+// Explicit filters for `show`, an empty filter keeps every log
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFilter {
+    pub subject: Option<String>,
+    pub topic: Option<String>,
+    // Only logs whose date is unknown, so they can be fixed with `edit`
+    pub missing_date: bool,
+    // First day kept, in days since 1970-01-01
+    pub since: Option<i64>,
+}
+impl LogFilter {
+    pub fn is_empty(&self) -> bool {
+        self.subject.is_none() && self.topic.is_none() && !self.missing_date && self.since.is_none()
+    }
+
+    // Case insensitive substring matches
+    pub fn matches(&self, log: &Log) -> bool {
+        let contains = |value: &str, filter: &Option<String>| match filter {
+            Some(filter) => value.to_lowercase().contains(&filter.to_lowercase()),
+            None => true,
+        };
+
+        contains(&log.subject, &self.subject)
+            && (self.topic.is_none() || log.topics.iter().any(|x| contains(x, &self.topic)))
+            && (!self.missing_date || log.date == UNKNOWN_DATE)
+            && self
+                .since
+                .is_none_or(|since| log.day().is_some_and(|day| day >= since))
+    }
+
+    pub fn apply(&self, journals: &mut [Journal]) {
+        for journal in journals.iter_mut() {
+            journal.logs.retain(|log| self.matches(log));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    // Wraps long cells into several lines
+    Table,
+    // Truncates long cells, the default
+    Compact,
+    // No width constraints at all
+    Wide,
+}
+impl TableFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "table" => Some(Self::Table),
+            "compact" => Some(Self::Compact),
+            "wide" => Some(Self::Wide),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Subject,
+    Topic,
+    Date,
+    Questions,
+    Right,
+    Percentage,
+}
+impl SortField {
+    pub const NAMES: [&'static str; 6] = [
+        "subject",
+        "topic",
+        "date",
+        "questions",
+        "right",
+        "percentage",
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "subject" => Some(Self::Subject),
+            "topic" => Some(Self::Topic),
+            "date" => Some(Self::Date),
+            "questions" => Some(Self::Questions),
+            "right" => Some(Self::Right),
+            "percentage" => Some(Self::Percentage),
+            _ => None,
+        }
+    }
+
+    pub fn compare(self, a: &Log, b: &Log) -> std::cmp::Ordering {
+        match self {
+            Self::Subject => a.subject.to_lowercase().cmp(&b.subject.to_lowercase()),
+            Self::Topic => a
+                .topic_label()
+                .to_lowercase()
+                .cmp(&b.topic_label().to_lowercase()),
+            // Logs without a readable date go first
+            Self::Date => a.day().cmp(&b.day()),
+            Self::Questions => a.total_questions.cmp(&b.total_questions),
+            Self::Right => a.right_answers.cmp(&b.right_answers),
+            Self::Percentage => a.percentage.total_cmp(&b.percentage),
+        }
+    }
+}
+
+// `<field>[:asc|:desc]`, ascending when no direction is given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+impl SortKey {
+    pub fn parse(value: &str) -> Option<Self> {
+        let (name, direction) = match value.split_once(':') {
+            Some((name, direction)) => (name, Some(direction)),
+            None => (value, None),
+        };
+        let descending = match direction {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(_) => return None,
+        };
+
+        Some(Self {
+            field: SortField::from_name(&name.to_lowercase())?,
+            descending,
+        })
+    }
+
+    pub fn compare(self, a: &Log, b: &Log) -> std::cmp::Ordering {
+        let ordering = self.field.compare(a, b);
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+// Order of the journals themselves, apart from the order of their logs.
+// Bigger and better journals go first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalSort {
+    Name,
+    Logs,
+    Percentage,
+}
+impl JournalSort {
+    pub const NAMES: [&'static str; 3] = ["name", "logs", "percentage"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(Self::Name),
+            "logs" => Some(Self::Logs),
+            "percentage" => Some(Self::Percentage),
+            _ => None,
+        }
+    }
+}
+
+pub fn sort_journals(journals: &mut [Journal], by: JournalSort) {
+    match by {
+        JournalSort::Name => journals.sort_by_key(|x| x.name.to_lowercase()),
+        JournalSort::Logs => journals.sort_by_key(|x| std::cmp::Reverse(x.logs.len())),
+        JournalSort::Percentage => journals.sort_by(|a, b| {
+            let (_, _, a) = journal_totals(a);
+            let (_, _, b) = journal_totals(b);
+            b.total_cmp(&a)
+        }),
+    }
+}
+
+// Sorting and reversing are shared by every view listing logs. Reversing
+// comes last, so it flips the sorted order or the insertion order
+fn order_logs<T>(logs: &mut [T], log: impl Fn(&T) -> &Log, options: &ShowOptions) {
+    if let Some(key) = options.sort {
+        logs.sort_by(|a, b| key.compare(log(a), log(b)));
+    } else if options.sort_percentage {
+        logs.sort_by_key(|x| std::cmp::Reverse(log(x).percentage as i32));
+    }
+    if options.reverse {
+        logs.reverse();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStyle {
+    Rounded,
+    Ascii,
+    Markdown,
+    None,
+}
+impl TableStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rounded" => Some(Self::Rounded),
+            "ascii" => Some(Self::Ascii),
+            "markdown" => Some(Self::Markdown),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    fn has_top_border(self) -> bool {
+        matches!(self, Self::Rounded | Self::Ascii)
+    }
+
+    // The line that sets a footer row apart from the rows above it
+    fn footer_line(self) -> Option<Line> {
+        match self {
+            Self::Rounded => Some(Line::new(Some('─'), Some('┼'), Some('├'), Some('┤'))),
+            Self::Ascii => Some(Line::new(Some('='), Some('+'), Some('+'), Some('+'))),
+            Self::Markdown | Self::None => None,
+        }
+    }
+}
+
+fn style_table(table: &mut Table, style: TableStyle) {
+    match style {
+        TableStyle::Rounded => table.with(Style::rounded()),
+        TableStyle::Ascii => table.with(Style::ascii()),
+        TableStyle::Markdown => table.with(Style::markdown()),
+        TableStyle::None => table.with(Style::empty()),
+    };
+}
+
+// Styles and fits the table, the title goes on the top border when there is
+// one and on its own line otherwise
+fn render_table(mut table: Table, title: &str, style: TableStyle, format: TableFormat) -> String {
+    style_table(&mut table, style);
+    title_table(table, title, style, format)
+}
+
+fn title_table(mut table: Table, title: &str, style: TableStyle, format: TableFormat) -> String {
+    if style.has_top_border() {
+        table.with(BorderText::new(0, title));
+        fit_table(&mut table, format);
+        return table.to_string();
+    }
+
+    fit_table(&mut table, format);
+    if title.is_empty() {
+        return table.to_string();
+    }
+    format!("{}\n{table}", title.trim_end())
+}
+
+// The widest columns are shrunk first until the table fits the terminal
+fn fit_table(table: &mut Table, format: TableFormat) {
+    let width = utils::terminal_width().unwrap_or(utils::DEFAULT_TERMINAL_WIDTH);
+
+    match format {
+        TableFormat::Table => {
+            table.with(Width::wrap(width).keep_words().priority::<PriorityMax>());
+        }
+        TableFormat::Compact => {
+            table.with(
+                Width::truncate(width)
+                    .suffix("...")
+                    .priority::<PriorityMax>(),
+            );
+        }
+        TableFormat::Wide => (),
+    }
+}
+
+pub fn show_journals(
+    out: &mut impl Write,
+    journals: &mut [Journal],
+    options: &ShowOptions,
+) -> std::io::Result<()> {
+    for journal in journals.iter_mut() {
+        if journal.logs.is_empty() {
+            writeln!(
+                out,
+                "{}",
                 journal
-                    .logs
-                    .sort_by(|b, a| (a.percentage as i32).cmp(&(b.percentage as i32)));
+                    .color_header(journal.name.clone(), &journal.name)
+                    .bold()
+            )?;
+            writeln!(out, "{}", text("no_logs_yet").dimmed())?;
+            continue;
+        }
+
+        order_logs(&mut journal.logs, |log| log, options);
+        writeln!(out, "{}", render_journal(journal, options))?;
+        if options.sparkline {
+            writeln!(
+                out,
+                "{}: {}",
+                text("header_trend").bold(),
+                utils::sparkline(&chronological_percentages(journal))
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+// Oldest first whatever order the logs are shown in, logs without a date go
+// by when they were created
+fn chronological_percentages(journal: &Journal) -> Vec<f32> {
+    let mut logs: Vec<&Log> = journal.logs.iter().collect();
+    logs.sort_by_key(|log| (log.day(), log.created_at));
+    logs.iter().map(|log| log.percentage).collect()
+}
+
+// A journal's logs in the order they are stored, followed by the totals row
+pub fn render_journal(journal: &Journal, options: &ShowOptions) -> String {
+    let (format, style) = (options.format, options.style);
+    let (sum_questions, sum_answers, percentage) = journal_totals(journal);
+    let mut total = vec![
+        text("header_total").to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+        sum_questions.to_string(),
+        sum_answers.to_string(),
+        utils::format_percentage(percentage, utils::PERCENTAGE_PRECISION),
+    ];
+
+    // The resource column only shows up once there is something to put in it
+    let mut builder = if journal.logs.iter().any(|log| log.resource.is_some()) {
+        total.insert(ResourceLog::INDEX, String::new());
+        Table::builder(journal.logs.iter().map(ResourceLog))
+    } else {
+        Table::builder(&journal.logs)
+    };
+    builder.add_record(total);
+
+    let mut table = builder.build();
+    let mut percentages: Vec<f32> = journal.logs.iter().map(|log| log.percentage).collect();
+    percentages.push(percentage);
+    color_subjects(&mut table, &journal.logs);
+    color_scale(&mut table, &percentages, options);
+    style_table(&mut table, style);
+    if let Some(line) = style.footer_line() {
+        let footer = table.count_rows() - 1;
+        table.with(HorizontalLine::new(footer, line));
+    }
+
+    let table = title_table(
+        table,
+        &format!("{name} ", name = journal.name),
+        style,
+        format,
+    );
+
+    journal.color_header(table, &journal.name)
+}
+
+// Paints the Subject cells of the logs with the colors from the config. Row
+// `n + 1` holds `logs[n]`, so header and footer cells are never matched
+fn color_subjects(table: &mut Table, logs: &[Log]) {
+    for (index, log) in logs.iter().enumerate() {
+        if let Some(color) = utils::subject_color(&log.subject) {
+            table.with(
+                Modify::new(Cell(index + 1, 0))
+                    .with(Format::new(move |cell| cell.color(color).to_string())),
+            );
+        }
+    }
+}
+
+// Paints each row under `show --color-scale` with the color of its
+// percentage, `percentages[n]` being the one of row `n + 1`. Cells already
+// painted by `color_subjects` keep their color, as the innermost one wins
+fn color_scale(table: &mut Table, percentages: &[f32], options: &ShowOptions) {
+    if !options.color_scale {
+        return;
+    }
+    let truecolor = utils::supports_truecolor();
+
+    for (index, percentage) in percentages.iter().enumerate() {
+        let color = utils::percentage_color(*percentage, truecolor);
+        table.with(
+            Modify::new(Rows::single(index + 1))
+                .with(Format::new(move |cell| cell.color(color).to_string())),
+        );
+    }
+}
+
+pub fn show_legend(subject_colors: &HashMap<String, String>) {
+    if subject_colors.is_empty() {
+        println!("{}", text("no_subject_colors").dimmed());
+        return;
+    }
+
+    let mut colors: Vec<_> = subject_colors.iter().collect();
+    colors.sort();
+    for (subject, color) in colors {
+        let painted = match utils::parse_color(color) {
+            Some(c) => subject.color(c),
+            None => subject.normal(),
+        };
+        println!("{painted} → {color}");
+    }
+}
+
+// A log next to the journal it belongs to, for the combined view
+struct JournalLog<'a> {
+    journal: &'a str,
+    log: &'a Log,
+}
+impl Tabled for JournalLog<'_> {
+    const LENGTH: usize = Log::LENGTH + 1;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let mut fields = vec![Cow::Borrowed(self.journal)];
+        fields.extend(self.log.fields());
+        fields
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        let mut headers = vec![Cow::Borrowed(text("header_journal"))];
+        headers.extend(Log::headers());
+        headers
+    }
+}
+
+// A log with its resource next to the UID, for journals where some log has one
+struct ResourceLog<'a>(&'a Log);
+impl ResourceLog<'_> {
+    // Where the resource column goes among the log columns
+    const INDEX: usize = 4;
+}
+impl Tabled for ResourceLog<'_> {
+    const LENGTH: usize = Log::LENGTH + 1;
+
+    fn fields(&self) -> Vec<Cow<'_, str>> {
+        let resource = self.0.resource.as_deref().unwrap_or_default();
+        let mut fields = self.0.fields();
+        fields.insert(
+            Self::INDEX,
+            Cow::Owned(utils::shorten(resource, utils::RESOURCE_WIDTH)),
+        );
+        fields
+    }
+
+    fn headers() -> Vec<Cow<'static, str>> {
+        let mut headers = Log::headers();
+        headers.insert(Self::INDEX, Cow::Borrowed(text("header_resource")));
+        headers
+    }
+}
+
+pub fn show_all(
+    out: &mut impl Write,
+    journals: &[Journal],
+    options: &ShowOptions,
+) -> std::io::Result<()> {
+    let mut rows: Vec<JournalLog> = journals
+        .iter()
+        .flat_map(|journal| {
+            journal.logs.iter().map(|log| JournalLog {
+                journal: &journal.name,
+                log,
+            })
+        })
+        .collect();
+
+    if rows.is_empty() {
+        return writeln!(out, "{}", text("no_logs_yet").dimmed());
+    }
+
+    // Logs from every journal are interleaved in the order they were taken
+    rows.sort_by_key(|row| row.log.created_at);
+    order_logs(&mut rows, |row| row.log, options);
+
+    let mut table = Table::new(&rows);
+    let percentages: Vec<f32> = rows.iter().map(|row| row.log.percentage).collect();
+    color_scale(&mut table, &percentages, options);
+    writeln!(
+        out,
+        "{}",
+        render_table(table, "", options.style, options.format)
+    )
+}
+
+// One tab separated line for `show --porcelain`: journal, uid, date, subject,
+// topic, questions, right answers and percentage. Scripts rely on this order,
+// new fields only ever go at the end. Tabs and newlines inside fields become
+// spaces so every log stays on a single line
+pub fn porcelain_line(journal: &str, log: &Log) -> String {
+    let clean = |field: &str| field.replace(['\t', '\n', '\r'], " ");
+
+    [
+        clean(journal),
+        clean(&log.uid),
+        clean(&log.date),
+        clean(&log.subject),
+        clean(&log.topic_label()),
+        log.total_questions.to_string(),
+        log.right_answers.to_string(),
+        format!("{:.2}", log.percentage),
+    ]
+    .join("\t")
+}
+
+// Every log without colors, borders or headers, in the same order as `--all`
+pub fn show_porcelain(
+    out: &mut impl Write,
+    journals: &[Journal],
+    options: &ShowOptions,
+) -> std::io::Result<()> {
+    let mut rows: Vec<JournalLog> = journals
+        .iter()
+        .flat_map(|journal| {
+            journal.logs.iter().map(|log| JournalLog {
+                journal: &journal.name,
+                log,
+            })
+        })
+        .collect();
+
+    rows.sort_by_key(|row| row.log.created_at);
+    order_logs(&mut rows, |row| row.log, options);
+
+    for row in rows {
+        writeln!(out, "{}", porcelain_line(row.journal, row.log))?;
+    }
+
+    Ok(())
+}
+
+// The `count` most recently taken logs across every journal, newest first
+pub fn show_recent(journals: &[Journal], count: usize) {
+    let mut rows: Vec<JournalLog> = journals
+        .iter()
+        .flat_map(|journal| {
+            journal.logs.iter().map(|log| JournalLog {
+                journal: &journal.name,
+                log,
+            })
+        })
+        .collect();
+
+    if rows.is_empty() {
+        println!("{}", text("no_logs_yet").dimmed());
+        return;
+    }
+
+    // Reversed first so logs added in the same second keep newest first
+    rows.reverse();
+    rows.sort_by_key(|row| std::cmp::Reverse(row.log.created_at));
+    rows.truncate(count);
+
+    println!(
+        "{}",
+        render_table(
+            Table::new(&rows),
+            "",
+            TableStyle::Rounded,
+            TableFormat::Compact
+        )
+    );
+}
+
+// The lowest scoring logs first, logs without questions say nothing about
+// what to review so they are left out
+pub fn show_weak(journals: &[Journal], count: usize) {
+    let mut rows: Vec<JournalLog> = journals
+        .iter()
+        .flat_map(|journal| {
+            journal
+                .logs
+                .iter()
+                .filter(|log| log.total_questions > 0)
+                .map(|log| JournalLog {
+                    journal: &journal.name,
+                    log,
+                })
+        })
+        .collect();
+
+    if rows.is_empty() {
+        println!("{}", text("no_logs_yet").dimmed());
+        return;
+    }
+
+    rows.sort_by(|a, b| a.log.percentage.total_cmp(&b.log.percentage));
+    rows.truncate(count);
+
+    let mut table = Table::new(&rows);
+    for header in [
+        "header_date",
+        "header_uid",
+        "header_questions",
+        "header_right_answers",
+    ] {
+        table.with(Disable::column(ByColumnName::new(text(header))));
+    }
+
+    println!(
+        "{}",
+        render_table(table, "", TableStyle::Rounded, TableFormat::Compact)
+    );
+}
+
+// A topic of a journal worth reviewing, as ranked by `study_plan`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanItem {
+    pub journal: String,
+    pub topic: String,
+    // Of the last session on the topic
+    pub percentage: f32,
+    pub days_since: i64,
+    pub score: f32,
+}
+
+// The `count` topics most in need of review. Each topic is judged by its last
+// session: the lower it scored and the longer ago it was, the higher it ranks,
+// so a topic left at 100% never shows up
+pub fn study_plan(journals: &[Journal], today: i64, count: usize) -> Vec<PlanItem> {
+    // A log covering several topics counts as the last session of each
+    let mut last: Vec<(&str, &str, &Log, i64)> = Vec::new();
+    for journal in journals {
+        for log in journal.logs.iter().filter(|log| log.total_questions > 0) {
+            let Some(day) = log.day() else { continue };
+            for topic in log.topics.iter() {
+                let same_topic = last.iter().position(|(name, other, _, _)| {
+                    *name == journal.name && other.to_lowercase() == topic.to_lowercase()
+                });
+                match same_topic {
+                    Some(index)
+                        if (last[index].3, last[index].2.created_at) > (day, log.created_at) => {}
+                    Some(index) => last[index] = (&journal.name, topic, log, day),
+                    None => last.push((&journal.name, topic, log, day)),
+                }
             }
         }
+    }
 
-        let mut table = Table::new(&journal.logs);
-        table
-            .with(
-                Modify::new(ByColumnName::new("Percentage").not(Rows::first()))
-                    .with(Format::new(|x| format!("{x}%"))),
-            )
-            .with(Style::rounded())
-            .with(BorderText::new(0, format!("{name} ", name = journal.name)))
-            .with(Modify::new(Rows::new(1..)).with(Width::truncate(15).suffix("...")))
-            .with(Width::justify(15));
+    let mut plan: Vec<PlanItem> = last
+        .into_iter()
+        .map(|(journal, topic, log, day)| {
+            let days_since = (today - day).max(0);
+            PlanItem {
+                journal: journal.to_string(),
+                topic: topic.to_string(),
+                percentage: log.percentage,
+                days_since,
+                score: (100.0 - log.percentage).max(0.0) * (days_since + 1) as f32,
+            }
+        })
+        .filter(|item| item.score > 0.0)
+        .collect();
+
+    plan.sort_by(|a, b| b.score.total_cmp(&a.score));
+    plan.truncate(count);
+    plan
+}
+
+pub fn show_plan(plan: &[PlanItem]) {
+    if plan.is_empty() {
+        println!("{}", text("nothing_to_plan").green());
+        return;
+    }
+
+    for (index, item) in plan.iter().enumerate() {
+        println!(
+            "{:>2}. {}/{} — {}, {}",
+            index + 1,
+            item.journal.bold(),
+            item.topic,
+            utils::format_percentage(item.percentage, utils::PERCENTAGE_PRECISION),
+            messages::days_ago(item.days_since).dimmed()
+        );
+    }
+}
+
+// Field by field changes between two versions of the same log, as
+// (header, old, new)
+pub fn diff_logs(old: &Log, new: &Log) -> Vec<(&'static str, String, String)> {
+    let fields = [
+        ("header_subject", old.subject.clone(), new.subject.clone()),
+        ("header_topic", old.topic_label(), new.topic_label()),
+        (
+            "header_date",
+            utils::display_date(&old.date),
+            utils::display_date(&new.date),
+        ),
+        (
+            "header_questions",
+            old.total_questions.to_string(),
+            new.total_questions.to_string(),
+        ),
+        (
+            "header_right_answers",
+            old.right_answers.to_string(),
+            new.right_answers.to_string(),
+        ),
+        ("header_tags", old.tags.join(", "), new.tags.join(", ")),
+        (
+            "header_resource",
+            old.resource.clone().unwrap_or_default(),
+            new.resource.clone().unwrap_or_default(),
+        ),
+        ("header_notes", old.notes.clone(), new.notes.clone()),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(key, old, new)| (text(key), old, new))
+        .collect()
+}
+
+// Prints each changed field with the old value in red and the new one in green
+pub fn show_log_changes(old: &Log, new: &Log) {
+    for (field, old, new) in diff_logs(old, new) {
+        println!("{field}: {} → {}", old.red(), new.green());
+    }
+}
+
+// Prints what would change going from `ours` to `theirs`, journals are matched
+// by name and logs by UID
+pub fn show_diff(ours: &[Journal], theirs: &[Journal]) {
+    let mut differences = 0;
+
+    for journal in ours.iter() {
+        if !theirs.iter().any(|x| x.name == journal.name) {
+            println!(
+                "{}",
+                format!("- {}", text_with("diff_journal", &[&journal.name])).red()
+            );
+            differences += 1;
+        }
+    }
+    for journal in theirs.iter() {
+        if !ours.iter().any(|x| x.name == journal.name) {
+            println!(
+                "{}",
+                format!("+ {}", text_with("diff_journal", &[&journal.name])).green()
+            );
+            differences += 1;
+        }
+    }
+
+    for our_journal in ours.iter() {
+        let their_journal = match theirs.iter().find(|x| x.name == our_journal.name) {
+            Some(journal) => journal,
+            None => continue,
+        };
+        let name = &our_journal.name;
+
+        for log in our_journal.logs.iter() {
+            match their_journal.logs.iter().find(|x| x.uid == log.uid) {
+                Some(their_log) => {
+                    for (field, old, new) in diff_logs(log, their_log) {
+                        let line = text_with("diff_changed", &[&log.uid, &name, &field]);
+                        println!("{}", format!("~ {line}: {old} → {new}").yellow());
+                        differences += 1;
+                    }
+                }
+                None => {
+                    let line = text_with(
+                        "diff_log",
+                        &[&log.uid, &log.subject, &log.topic_label(), &name],
+                    );
+                    println!("{}", format!("- {line}").red());
+                    differences += 1;
+                }
+            }
+        }
+        for log in their_journal.logs.iter() {
+            if !our_journal.logs.iter().any(|x| x.uid == log.uid) {
+                let line = text_with(
+                    "diff_log",
+                    &[&log.uid, &log.subject, &log.topic_label(), &name],
+                );
+                println!("{}", format!("+ {line}").green());
+                differences += 1;
+            }
+        }
+    }
+
+    if differences == 0 {
+        println!("{}", text("no_differences").green());
+    }
+}
+
+pub fn show_log(log: &Log) {
+    println!("{}", render_log(log));
+}
+
+// A single log as `get <UID>` prints it, with its tags and notes below
+pub fn render_log(log: &Log) -> String {
+    let mut rendered = Table::new(vec![log])
+        .with(Disable::column(ByColumnName::new(text("header_subject"))))
+        .with(Style::rounded())
+        .with(BorderText::new(0, log.subject.clone()))
+        .to_string();
+
+    if !log.tags.is_empty() {
+        rendered.push_str(&format!(
+            "\n{}: {}",
+            text("header_tags").bold(),
+            log.tags.join(", ")
+        ));
+    }
+    if !log.notes.is_empty() {
+        rendered.push_str(&format!(
+            "\n{}:\n{}",
+            text("header_notes").bold(),
+            log.notes
+        ));
+    }
+
+    rendered
+}
+
+const REQUIRED_FIELDS: [&str; 4] = ["Subject", "Topic", "Total Questions", "Right Answers"];
+
+// Optional, left as `[]` when there is no link
+const RESOURCE_FIELD: &str = "Resource (URL)";
+
+// Whether the note has the field at all, notes from templates without it
+// keep the value the log had
+fn has_field(buf: &str, field: &str) -> bool {
+    buf.lines().any(|line| line.trim() == field)
+}
 
-        println!("{table}", table = table.to_string());
+// The value under a field header, a field still holding the template
+// placeholder or left empty cancels the whole log
+fn field_value(field: &str, line: &str) -> Result<String, ()> {
+    if line.trim() == "[type here]" {
+        eprintln!("{}", text_with("field_unchanged_named", &[&field]).red());
+        return Err(());
     }
-}
 
-pub fn show_log(log: &Log) {
-    let table = Table::new(vec![log])
-        .with(Disable::column(ByColumnName::new("Subject")))
-        .with(Style::rounded())
-        .with(BorderText::new(0, format!("{}", log.subject)))
-        .to_string();
+    let value = utils::remove_brackets(line);
+    if value.is_empty() {
+        eprintln!("{}", text_with("field_left_blank", &[&field]).red());
+        return Err(());
+    }
 
-    println!("{table}");
+    Ok(value)
 }
 
-fn log_from_tf(buf: String) -> Result<Log, ()> {
+pub fn log_from_tf(buf: String) -> Result<Log, ()> {
     let mut lines = buf.lines().enumerate().peekable();
     let mut log: Log = Log::new();
+    let mut found: Vec<&str> = Vec::new();
 
     while let Some(current) = lines.next() {
         if let Some(&next) = lines.peek() {
             let next_line = next.1;
             let line_number = next.0 + 1;
 
-            let mut quit = false;
-
-            match current.1.trim() {
-                "[type here]" => quit = true,
-                "Subject" => log.subject = utils::remove_brackets(next_line),
-                "Topic" => log.topic = utils::remove_brackets(next_line),
+            let field = current.1.trim();
+            if field == "[type here]" {
+                eprintln!("{}", text("field_unchanged").red());
+                return Err(());
+            }
+            if field == RESOURCE_FIELD {
+                let value = utils::remove_brackets(next_line);
+                log.resource = (!value.is_empty()).then_some(value);
+                continue;
+            }
+            if !REQUIRED_FIELDS.contains(&field) {
+                continue;
+            }
 
-                "Total Questions" => {
-                    log.total_questions =
-                        utils::remove_brackets(next_line).parse().map_err(|err| {
-                            eprintln!(
-                                "{}: Failed to read log file: {err} {next_line} at line {}",
-                                "ERROR".red(),
-                                line_number
-                            )
-                        })?
-                }
-                "Right Answers" => {
-                    log.right_answers =
-                        utils::remove_brackets(next_line).parse().map_err(|err| {
-                            eprintln!(
-                                "{}: Failed to read log file: {err} {next_line} at line {}",
-                                "ERROR".red(),
-                                line_number
-                            )
-                        })?
+            let value = field_value(field, next_line)?;
+            let parse_failed = |err: std::num::ParseIntError| {
+                // Decimals are a common typo, name the field instead of
+                // surfacing the raw parse error
+                if value.parse::<f64>().is_ok() {
+                    eprintln!(
+                        "{}: {}",
+                        text("error").red(),
+                        text_with("not_whole_number", &[&field, &value])
+                    );
+                    return;
                 }
-                _ => (),
-            }
-            if quit == true {
                 eprintln!(
-                    "{text}",
-                    text = "a field was left unchanged, log was not added".red()
-                );
-                return Err(());
+                    "{}: {}",
+                    text("error").red(),
+                    text_with("read_log_file_failed", &[&err, &next_line, &line_number])
+                )
+            };
+
+            match field {
+                "Subject" => log.subject = value,
+                "Topic" => log.topics = utils::parse_topics(&value),
+                "Total Questions" => log.total_questions = value.parse().map_err(parse_failed)?,
+                "Right Answers" => log.right_answers = value.parse().map_err(parse_failed)?,
+                _ => (),
             }
+            found.push(field);
         }
     }
 
+    if let Some(field) = REQUIRED_FIELDS.iter().find(|field| !found.contains(field)) {
+        eprintln!("{}", text_with("field_left_blank", &[field]).red());
+        return Err(());
+    }
+
     log.percentage = utils::get_percentage(log.right_answers as f32, log.total_questions as f32);
 
     Ok(log)
 }
 
-pub fn make_log(name: &str) -> Result<Log, ()> {
+// Writes `content` into a temporary file, opens it in the user's editor and
+// returns whatever was saved.
+fn edit_in_tempfile(content: &str) -> Result<String, ()> {
+    edit_note(content).map_err(|_| ())
+}
+
+// Same as `edit_in_tempfile`, but when the editor fails or Ctrl-C is pressed
+// the error holds what the file had by then, if it was changed at all
+fn edit_note(content: &str) -> Result<String, Option<String>> {
+    utils::check_interactive().map_err(|()| None)?;
+    utils::check_editor().map_err(|()| None)?;
+
     let mut tf = Builder::new()
         .prefix("stu-log_")
         .suffix(".txt")
         .rand_bytes(4)
         .tempfile()
         .map_err(|err| {
-            eprintln!("{}: Could not create tempfile: {err}", "ERROR".red());
+            eprintln!(
+                "{}: {}",
+                text("error").red(),
+                text_with("create_tempfile_failed", &[&err])
+            );
+            None
         })?;
 
-    let date = utils::get_date();
-    let note_builder_text: &str = &format!(
-        "\
-        STU Note Builder\n\
-        Journal: {name}\n\
-        Date: {date}\n\n\
-        \
-        ------------------\n\n\
-        **TYPE INSIDE BRACKETS**\n\
-        *edit, save and exit*\n\
-        *to cancel just leave some field unchanged*\n\n\
-        \
-        Subject\n\
-        [type here]\n\n\
-        \
-        Topic\n\
-        [type here]\n\n\
-        \
-        Total Questions\n\
-        [type here]\n\n\
-        \
-        Right Answers\n\
-        [type here]\n\
-        "
-    );
-
-    write!(tf, "{}", &note_builder_text).unwrap();
+    write!(tf, "{}", content).unwrap();
     tf.flush().unwrap();
 
-    utils::edit_text(tf.path().display().to_string())?;
+    let interrupts = utils::InterruptGuard::new();
+    let edited = utils::edit_text(tf.path());
+    let interrupted = interrupts.interrupted();
+    drop(interrupts);
+    if interrupted || edited.is_err() {
+        if interrupted {
+            eprintln!("{}", text("edit_interrupted").red());
+        }
+        // Returning drops `tf`, which deletes the file
+        let buf = fs::read_to_string(tf.path()).unwrap_or_default();
+        return Err((!buf.is_empty() && buf != content).then_some(buf));
+    }
 
     tf.flush().unwrap();
     tf.rewind().unwrap();
@@ -296,109 +2154,550 @@ pub fn make_log(name: &str) -> Result<Log, ()> {
     let mut buf = String::new();
     tf.read_to_string(&mut buf).unwrap();
 
+    tf.close().map_err(|err| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("delete_tempfile_failed", &[&err])
+        );
+        None
+    })?;
+
+    // Quitting without saving, `:cq` or a killed editor all leave the file as
+    // it was written, which is taken as a cancel
+    if hash_text(&buf) == hash_text(content) {
+        eprintln!("{}", text("no_changes").red());
+        return Err(None);
+    }
+
+    Ok(buf)
+}
+
+fn hash_text(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+// `{journal}` and `{date}` are filled in when the note is opened. Users can
+// override it with a `template.txt` in the data directory, `log_from_tf`
+// only cares about the field header lines.
+pub const NOTE_TEMPLATE: &str = "\
+    STU Note Builder\n\
+    Journal: {journal}\n\
+    Date: {date}\n\n\
+    \
+    ------------------\n\n\
+    **TYPE INSIDE BRACKETS**\n\
+    *edit, save and exit*\n\
+    *to cancel just leave some field unchanged*\n\n\
+    \
+    Subject\n\
+    [type here]\n\n\
+    \
+    Topic\n\
+    [type here]\n\n\
+    \
+    Total Questions\n\
+    [type here]\n\n\
+    \
+    Right Answers\n\
+    [type here]\n\n\
+    \
+    Resource (URL)\n\
+    []\n\
+    ";
+
+fn custom_template(data_dir: &Path) -> Option<String> {
+    fs::read_to_string(utils::template_path(data_dir)).ok()
+}
+
+// Replaces the bracketed line after each known field header with the log's
+// value, wherever the header is in the template.
+fn fill_template(template: &str, log: &Log) -> String {
+    fill_fields(
+        template,
+        &[
+            ("Subject", log.subject.clone()),
+            ("Topic", log.topic_label()),
+            ("Total Questions", log.total_questions.to_string()),
+            ("Right Answers", log.right_answers.to_string()),
+            (RESOURCE_FIELD, log.resource.clone().unwrap_or_default()),
+        ],
+    )
+}
+
+fn fill_fields(template: &str, values: &[(&str, String)]) -> String {
+    let mut lines: Vec<String> = template.lines().map(|x| x.to_string()).collect();
+
+    for i in 1..lines.len() {
+        let value = match values
+            .iter()
+            .find(|(field, _)| *field == lines[i - 1].trim())
+        {
+            Some((_, value)) => value,
+            None => continue,
+        };
+        if lines[i].trim().starts_with('[') {
+            lines[i] = format!("[{value}]");
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+// The note `add` opens for a new log, from `template.txt` when there is one
+fn note_template(journal_name: &str, date: &str, data_dir: &Path) -> String {
+    custom_template(data_dir)
+        .unwrap_or_else(|| NOTE_TEMPLATE.to_string())
+        .replace("{journal}", journal_name)
+        .replace("{date}", &utils::display_date(date))
+}
+
+// Prints the note a new log would start from, for checking a custom template.
+// Where it comes from goes to stderr so the template can be piped
+pub fn preview_template(data_dir: &Path) {
+    let path = utils::template_path(data_dir);
+    if custom_template(data_dir).is_some() {
+        eprintln!(
+            "{}",
+            text_with("template_from_file", &[&path.display()]).dimmed()
+        );
+    } else {
+        eprintln!("{}", text("template_builtin").dimmed());
+    }
+
+    print!(
+        "{}",
+        note_template(
+            text("template_example_journal"),
+            &utils::get_date(),
+            data_dir
+        )
+    );
+}
+
+// `seed` pre-fills the subject and topic, e.g. when studying a topic again.
+// The counts are always left for the new session
+pub fn make_log(name: &str, date: &str, seed: Option<&Log>, data_dir: &Path) -> Result<Log, ()> {
+    // Before offering the draft, the prompt would otherwise wait on a pipe
+    utils::check_interactive()?;
+
+    let note_builder_text: &str = &match read_draft(name, data_dir) {
+        Some(draft) if utils::confirm(text("resume_draft")) => draft,
+        _ => {
+            let template = note_template(name, date, data_dir);
+            match seed {
+                Some(seed) => fill_fields(
+                    &template,
+                    &[
+                        ("Subject", seed.subject.clone()),
+                        ("Topic", seed.topic_label()),
+                    ],
+                ),
+                None => template,
+            }
+        }
+    };
+
+    // Leaving a field unchanged is how a note is cancelled, so only a note
+    // the editor lost is kept as a draft
+    let buf = edit_note(note_builder_text).map_err(|lost| {
+        if let Some(buf) = lost {
+            save_draft(name, &buf, data_dir);
+        }
+    })?;
     let mut log: Log = log_from_tf(buf)?;
-    log.date = date;
+    log.date = date.to_string();
+    apply_tag_rules(&mut log, &TagRules::load(data_dir)?);
 
-    tf.close().map_err(|err| {
-        eprintln!("{}: Could not delete temporary file: {err}", "ERROR".red());
+    Ok(log)
+}
+
+// Same as `make_log` with the filled in note piped in instead of typed in the
+// editor
+pub fn log_from_stdin(date: &str, data_dir: &Path) -> Result<Log, ()> {
+    let mut buf = String::new();
+    std::io::stdin().read_to_string(&mut buf).map_err(|err| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("read_stdin_failed", &[&err])
+        );
     })?;
 
+    let mut log: Log = log_from_tf(buf)?;
+    log.date = date.to_string();
+    apply_tag_rules(&mut log, &TagRules::load(data_dir)?);
+
     Ok(log)
 }
 
-pub fn list_journals(journals: &Vec<Journal>) {
+// A note whose editor failed or was interrupted is kept in `draft.txt`, the
+// first line being the journal it was meant for, so `stu add` can offer it again
+fn save_draft(journal_name: &str, note: &str, data_dir: &Path) {
+    let path = utils::draft_path(data_dir);
+    match fs::write(&path, format!("{journal_name}\n{note}")) {
+        Ok(()) => eprintln!("{}", text_with("draft_saved", &[&journal_name])),
+        Err(err) => eprintln!(
+            "{}: {}",
+            text("warning").yellow(),
+            text_with("save_draft_failed", &[&path.display(), &err])
+        ),
+    }
+}
+
+fn read_draft(journal_name: &str, data_dir: &Path) -> Option<String> {
+    let draft = fs::read_to_string(utils::draft_path(data_dir)).ok()?;
+    let (name, note) = draft.split_once('\n')?;
+    (name == journal_name).then(|| note.to_string())
+}
+
+// Called once the log made from the draft was saved
+pub fn clear_draft(data_dir: &Path) {
+    let _ = fs::remove_file(utils::draft_path(data_dir));
+}
+
+pub fn apply_tag_rules(log: &mut Log, rules: &TagRules) {
+    let mut fields = vec![log.subject.as_str()];
+    fields.extend(log.topics.iter().map(|x| x.as_str()));
+    for tag in rules.tags_for(&fields) {
+        if !log.tags.contains(&tag) {
+            log.tags.push(tag);
+        }
+    }
+}
+
+// Reads logs from a CSV/TSV file, every log gets `date` and an UID not used by
+// any other log
+pub fn import_logs(
+    path: &str,
+    journals: &[Journal],
+    date: &str,
+    data_dir: &Path,
+) -> Result<Vec<Log>, ()> {
+    let content = fs::read_to_string(path).map_err(|err| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("read_import_file_failed", &[&path, &err])
+        );
+    })?;
+
+    let tag_rules = TagRules::load(data_dir)?;
+    let mut uids: Vec<String> = journals
+        .iter()
+        .flat_map(|journal| journal.logs.iter().map(|log| log.uid.clone()))
+        .collect();
+    let mut logs: Vec<Log> = Vec::new();
+
+    for (index, row) in content.lines().enumerate() {
+        let row = row.trim();
+        let is_header = index == 0 && row.to_lowercase().starts_with("subject");
+        if row.is_empty() || is_header {
+            continue;
+        }
+
+        let (subject, topic, total_questions, right_answers) = utils::parse_log_row(row)
+            .ok_or_else(|| {
+                eprintln!(
+                    "{}: {}",
+                    text("error").red(),
+                    text_with("invalid_import_row", &[&(index + 1), &row])
+                );
+            })?;
+
+        let mut log = Log::new();
+        while uids.contains(&log.uid) {
+            log = Log::new();
+        }
+        uids.push(log.uid.clone());
+
+        log.subject = subject;
+        log.topics = utils::parse_topics(&topic);
+        log.date = date.to_string();
+        log.total_questions = total_questions;
+        log.right_answers = right_answers;
+        log.percentage = utils::get_percentage(right_answers as f32, total_questions as f32);
+        apply_tag_rules(&mut log, &tag_rules);
+        logs.push(log);
+    }
+
+    if logs.is_empty() {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("empty_import_file", &[&path])
+        );
+        return Err(());
+    }
+
+    Ok(logs)
+}
+
+pub fn init_template(data_dir: &Path) -> Result<(), ()> {
+    let path = utils::template_path(data_dir);
+    if path.exists() {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("template_exists", &[&path.display()])
+        );
+        return Err(());
+    }
+
+    let mut file = File::create(&path).map_err(|err| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("create_file_failed", &[&err])
+        );
+    })?;
+    write!(file, "{NOTE_TEMPLATE}").unwrap();
+
+    Ok(())
+}
+
+pub fn list_journals(journals: &[Journal]) {
     let buf = format!(
         "{} {} {}",
-        "There's".bold(),
+        text("list_there_is").bold(),
         journals.len().to_string().red().bold(),
-        "Journals in database".bold()
+        text("list_journals_in_database").bold()
     );
     println!("{buf}\n");
     for journal in journals.iter() {
         println!("- {name}", name = journal.name.bold());
-        println!("    Logs");
-        for logs in journal.logs.iter() {
-            println!("       uid: {id} [{date}]", id = logs.uid, date = logs.date);
+        println!("    {}", text("list_logs"));
+        for log in journal.logs.iter() {
+            println!(
+                "       uid: {id} {subject} {date}",
+                id = log.uid.cyan(),
+                subject = log.subject,
+                date = format!("[{}]", utils::display_date(&log.date)).dimmed()
+            );
         }
     }
     println!();
 }
 
-pub fn sync_data(journals: String, filepath: &str) -> Result<(), ()> {
+pub fn write_journals(journals: &[Journal], filepath: impl AsRef<Path>) -> Result<(), ()> {
+    let data = DataFile {
+        schema: SCHEMA_VERSION,
+        journals,
+    };
+    let json_content = serde_json::to_string(&data).map_err(|err| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("serialize_failed", &[&err])
+        )
+    })?;
+
+    sync_data(json_content, filepath)
+}
+
+pub fn sync_data(journals: String, filepath: impl AsRef<Path>) -> Result<(), ()> {
     let mut file = File::create(filepath).map_err(|err| {
-        eprintln!("{}: Could not create file: {err}", "ERROR".red());
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("create_file_failed", &[&err])
+        );
     })?;
 
     write!(file, "{}", journals).unwrap();
 
     file.sync_all().map_err(|err| {
-        eprintln!("{}: Could not sync OS data: {err}", "ERROR".red());
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("sync_failed", &[&err])
+        );
     })?;
 
     Ok(())
 }
 
-pub fn query_for(str: &str, filepath: &str) -> Result<(), ()> {
-    let mut journals: Vec<Journal> = Vec::new();
-    get_journals(filepath, &mut journals)?;
-
+// A journal named `str` when there is one with logs, otherwise the logs whose
+// subject, topic or date is `str`
+fn query_matches(journals: Vec<Journal>, str: &str) -> Journal {
     let mut query_journal: Journal = Journal::new("Query");
     for journal in journals {
-        if journal.name.to_lowercase() == str {
-            show_journals(&mut vec![journal]);
-            return Ok(());
+        let is_named = journal.name.to_lowercase() == str
+            || journal
+                .aliases
+                .iter()
+                .any(|alias| alias.to_lowercase() == str);
+        if is_named && !journal.logs.is_empty() {
+            return journal;
         }
         for log in journal.logs.into_iter() {
             if str == log.subject.to_lowercase()
-                || str == log.topic.to_lowercase()
+                || log.has_topic(str)
                 || str == log.date.to_lowercase()
+                || str == utils::display_date(&log.date)
             {
                 query_journal.add_log(log);
             }
         }
     }
-    if query_journal.logs.len() > 0 {
-        show_journals(&mut vec![query_journal]);
+
+    query_journal
+}
+
+pub fn query_for(str: &str, filepath: &Path, options: &ShowOptions) -> Result<(), ()> {
+    let mut journals: Vec<Journal> = Vec::new();
+    get_journals(filepath, &mut journals)?;
+    hide_archived(&mut journals, options);
+
+    let query_journal = query_matches(journals, str);
+    if !query_journal.logs.is_empty() {
+        println!("{}", messages::results(query_journal.logs.len()));
+        let _ = show_journals(&mut std::io::stdout().lock(), &mut [query_journal], options);
         return Ok(());
     }
 
-    eprintln!("{}", format!("unsuccessfully <{str}> query").red());
+    eprintln!("{}", text_with("query_failed", &[&str]).red());
     Err(())
 }
 
-pub fn query_uid(uid: &str, filepath: &str) -> Result<(), ()> {
+// Prints only how many logs `query_for` would show, failing when there are none
+pub fn count_for(str: &str, filepath: &Path, options: &ShowOptions) -> Result<(), ()> {
+    let mut journals = JournalStore::load_without_percentages(filepath)?.journals;
+    hide_archived(&mut journals, options);
+
+    let count = query_matches(journals, str).logs.len();
+    println!("{count}");
+    if count == 0 {
+        return Err(());
+    }
+    Ok(())
+}
+
+pub fn query_tag(tag: &str, filepath: &Path, options: &ShowOptions) -> Result<(), ()> {
     let mut journals: Vec<Journal> = Vec::new();
     get_journals(filepath, &mut journals)?;
+    hide_archived(&mut journals, options);
 
-    let mut log: Option<Log> = None;
-    for journal in journals.iter() {
-        log = journal.logs.iter().cloned().filter(|x| x.uid == uid).next();
-        if log.is_some() {
-            break;
+    let mut query_journal: Journal = Journal::new("Query");
+    for journal in journals {
+        for log in journal.logs.into_iter() {
+            if log.tags.iter().any(|log_tag| log_tag.to_lowercase() == tag) {
+                query_journal.add_log(log);
+            }
         }
     }
+    if !query_journal.logs.is_empty() {
+        println!("{}", messages::results(query_journal.logs.len()));
+        let _ = show_journals(&mut std::io::stdout().lock(), &mut [query_journal], options);
+        return Ok(());
+    }
 
-    if log.is_none() {
-        eprintln!("{}", format!("log with <{uid}> UID not found").red());
-        return Err(());
+    eprintln!("{}", text_with("tag_query_failed", &[&tag]).red());
+    Err(())
+}
+
+pub fn query_uid(uid: &str, filepath: &Path) -> Result<(), ()> {
+    let mut journals: Vec<Journal> = Vec::new();
+    get_journals(filepath, &mut journals)?;
+
+    let uid = resolve_uid_prefix(&journals, uid)?;
+    match find_log_mut(&mut journals, &uid) {
+        Some((journal_index, log_index)) => {
+            println!("{}", messages::results(1));
+            show_log(&journals[journal_index].logs[log_index]);
+            Ok(())
+        }
+        None => {
+            eprintln!("{}", text_with("uid_not_found", &[&uid]).red());
+            Err(())
+        }
     }
+}
 
-    show_log(&log.unwrap());
-    return Ok(());
+// The full UID of the only log whose UID starts with `prefix`, like a short
+// git hash. A UID typed out in full wins over the longer ones starting with it
+pub fn resolve_uid_prefix(journals: &[Journal], prefix: &str) -> Result<String, ()> {
+    match_uid_prefix(journals, prefix).map_err(|uids| uid_prefix_missed(prefix, &uids))
 }
 
-pub fn edit_log(log: Log) -> Result::<Log, ()> {
-    let mut tf = Builder::new()
-        .prefix("stu-log_")
-        .suffix(".txt")
-        .rand_bytes(4)
-        .tempfile()
-        .map_err(|err| {
-            eprintln!("{}: Could not create tempfile: {err}", "ERROR".red());
-        })?;
+// Same as `resolve_uid_prefix` without printing anything, the error holds
+// every UID starting with `prefix`: none when no log has it
+pub fn match_uid_prefix<'a>(journals: &'a [Journal], prefix: &str) -> Result<String, Vec<&'a str>> {
+    let uids: Vec<&str> = journals
+        .iter()
+        .flat_map(|journal| journal.logs.iter())
+        .map(|log| log.uid.as_str())
+        .filter(|uid| uid.starts_with(prefix))
+        .collect();
+    if uids.contains(&prefix) {
+        return Ok(prefix.to_string());
+    }
 
-    let note_builder_text: &str = &format!(
-        "\
+    match uids[..] {
+        [uid] => Ok(uid.to_string()),
+        _ => Err(uids),
+    }
+}
+
+// Why `prefix` didn't resolve, given the UIDs `match_uid_prefix` found for it
+pub fn uid_prefix_missed(prefix: &str, uids: &[&str]) {
+    if uids.is_empty() {
+        eprintln!("{}", text_with("uid_not_found", &[&prefix]).red());
+    } else {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with(
+                "ambiguous_uid_prefix",
+                &[&prefix, &uids.len(), &uids.join(", ")]
+            )
+        );
+    }
+}
+
+// Returns the (journal, log) indexes of the log with the given UID
+pub fn find_log_mut(journals: &mut [Journal], uid: &str) -> Option<(usize, usize)> {
+    journals
+        .iter()
+        .enumerate()
+        .find_map(|(journal_index, journal)| {
+            journal
+                .logs
+                .iter()
+                .position(|log| log.uid == uid)
+                .map(|log_index| (journal_index, log_index))
+        })
+}
+
+// The (journal, log) indexes of the most recently created log, the later one
+// wins between logs created in the same second
+pub fn find_last_log(journals: &[Journal]) -> Option<(usize, usize)> {
+    journals
+        .iter()
+        .enumerate()
+        .flat_map(|(journal_index, journal)| {
+            journal
+                .logs
+                .iter()
+                .enumerate()
+                .map(move |(log_index, log)| (log.created_at, journal_index, log_index))
+        })
+        .max_by_key(|(created_at, _, _)| *created_at)
+        .map(|(_, journal_index, log_index)| (journal_index, log_index))
+}
+
+pub fn edit_log(log: Log, journal_name: &str, data_dir: &Path) -> Result<Log, ()> {
+    let template = custom_template(data_dir).map(|template| {
+        fill_template(&template, &log)
+            .replace("{journal}", journal_name)
+            .replace("{date}", &utils::display_date(&log.date))
+    });
+
+    let note_builder_text: &str = &template.unwrap_or_else(|| {
+        format!(
+            "\
         STU Note edit\n\n\
         ------------------\n\n\
         **TYPE INSIDE BRACKETS**\n\
@@ -415,35 +2714,201 @@ pub fn edit_log(log: Log) -> Result::<Log, ()> {
         [{questions}]\n\n\
         \
         Right Answers\n\
-        [{answers}]\n\
+        [{answers}]\n\n\
+        \
+        Resource (URL)\n\
+        [{resource}]\n\
         ",
-        subject   = log.subject,
-        topic     = log.topic,
-        questions = log.total_questions,
-        answers   = log.right_answers
-    );
+            subject = log.subject,
+            topic = log.topic_label(),
+            questions = log.total_questions,
+            answers = log.right_answers,
+            resource = log.resource.as_deref().unwrap_or_default()
+        )
+    });
 
-    write!(tf, "{}", &note_builder_text).unwrap();
-    tf.flush().unwrap();
+    edited_log(edit_in_tempfile(note_builder_text)?, &log)
+}
 
-    utils::edit_text(tf.path().display().to_string())?;
+// The log filled in `buf` as an edit of `log`, see `keep_unedited_fields`
+pub fn edited_log(buf: String, log: &Log) -> Result<Log, ()> {
+    let keep_resource = !has_field(&buf, RESOURCE_FIELD);
+    let mut new_log: Log = log_from_tf(buf)?;
+    keep_unedited_fields(&mut new_log, log, keep_resource);
+    Ok(new_log)
+}
 
-    tf.flush().unwrap();
-    tf.rewind().unwrap();
+// Everything the edit template doesn't show is kept from the log before the
+// edit, the resource too when the template has no field for it
+fn keep_unedited_fields(new_log: &mut Log, old_log: &Log, keep_resource: bool) {
+    if keep_resource {
+        new_log.resource = old_log.resource.clone();
+    }
+    new_log.uid = old_log.uid.clone();
+    new_log.date = old_log.date.clone();
+    new_log.notes = old_log.notes.clone();
+    new_log.tags = old_log.tags.clone();
+    new_log.created_at = old_log.created_at;
+    new_log.archived = old_log.archived;
+}
 
-    let mut buf = String::new();
-    tf.read_to_string(&mut buf).unwrap();
+// Each log is written as its own block, split by `---` lines and tagged with
+// a `UID:` marker so the block can be matched back to the original log.
+pub fn logs_from_tf(buf: String, logs: &[Log]) -> Result<Vec<Log>, ()> {
+    let mut new_logs: Vec<Log> = Vec::new();
 
-    let mut new_log: Log = log_from_tf(buf)?;
+    for block in buf.split("\n---\n") {
+        let uid = match block
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("UID:"))
+        {
+            Some(uid) => uid.trim(),
+            None => continue,
+        };
 
-    new_log.uid = log.uid;
-    new_log.date = log.date;
+        let old_log = logs.iter().find(|log| log.uid == uid).ok_or_else(|| {
+            eprintln!(
+                "{}: {}",
+                text("error").red(),
+                text_with("unknown_block_uid", &[&uid])
+            );
+        })?;
 
-    tf.close().map_err(|err| {
-        eprintln!("{}: Could not delete temporary file: {err}", "ERROR".red());
-    })?;
+        let mut new_log: Log = log_from_tf(block.to_string())?;
+        keep_unedited_fields(&mut new_log, old_log, !has_field(block, RESOURCE_FIELD));
+        new_logs.push(new_log);
+    }
 
-    Ok(new_log)
+    Ok(new_logs)
+}
+
+// Moves the logs of `source` into `dest` and removes `source` once it is
+// empty, logs whose UID is already in `dest` stay behind. Returns how many
+// logs were moved and how many stayed
+pub fn merge_journals(journals: &mut Vec<Journal>, source: usize, dest: usize) -> (usize, usize) {
+    let logs = std::mem::take(&mut journals[source].logs);
+    let (skipped, merged): (Vec<Log>, Vec<Log>) = logs
+        .into_iter()
+        .partition(|log| journals[dest].logs.iter().any(|x| x.uid == log.uid));
+    let counts = (merged.len(), skipped.len());
+
+    journals[dest].logs.extend(merged);
+    journals[source].logs = skipped;
+    if journals[source].logs.is_empty() {
+        journals.remove(source);
+    }
+
+    counts
+}
+
+// Sets the subject, or the topic when `topic` is set, of every log where it is
+// `old` ignoring case to `new`. Returns how many logs changed
+pub fn rename_field(journals: &mut [Journal], old: &str, new: &str, topic: bool) -> usize {
+    let old = old.trim().to_lowercase();
+    let mut count = 0;
+
+    for log in journals
+        .iter_mut()
+        .flat_map(|journal| journal.logs.iter_mut())
+    {
+        let fields = if topic {
+            log.topics.iter_mut().collect()
+        } else {
+            vec![&mut log.subject]
+        };
+        let mut changed = false;
+        for field in fields {
+            if field.trim().to_lowercase() == old {
+                *field = new.to_string();
+                changed = true;
+            }
+        }
+        count += changed as usize;
+    }
+
+    count
+}
+
+// Folds a log recorded twice into `log`: counts are summed, the earlier date
+// is kept and notes and tags are combined. `log` keeps its UID, and its
+// resource when it has one
+pub fn merge_logs(log: &mut Log, other: &Log) {
+    log.total_questions += other.total_questions;
+    log.right_answers += other.right_answers;
+    log.percentage = utils::get_percentage(log.right_answers as f32, log.total_questions as f32);
+
+    let other_is_earlier = match (log.day(), other.day()) {
+        (Some(day), Some(other_day)) => other_day < day,
+        (None, Some(_)) => true,
+        _ => false,
+    };
+    if other_is_earlier {
+        log.date = other.date.clone();
+    }
+    log.created_at = log.created_at.min(other.created_at);
+
+    if !other.notes.is_empty() && other.notes != log.notes {
+        if !log.notes.is_empty() {
+            log.notes.push('\n');
+        }
+        log.notes.push_str(&other.notes);
+    }
+    for tag in other.tags.iter() {
+        if !log.tags.contains(tag) {
+            log.tags.push(tag.clone());
+        }
+    }
+    if log.resource.is_none() {
+        log.resource = other.resource.clone();
+    }
+}
+
+pub fn edit_journal(journal: &Journal) -> Result<Vec<Log>, ()> {
+    let mut note_builder_text = format!(
+        "\
+        STU Journal edit\n\
+        Journal: {name}\n\n\
+        \
+        ------------------\n\n\
+        **TYPE INSIDE BRACKETS**\n\
+        *edit, save and exit*\n\
+        *do not change the UID lines*\n\
+        *to cancel just leave some field unchanged*\n\n\
+        ",
+        name = journal.name
+    );
+
+    for log in journal.logs.iter() {
+        note_builder_text.push_str(&format!(
+            "\
+            ---\n\
+            UID: {uid}\n\n\
+            \
+            Subject\n\
+            [{subject}]\n\n\
+            \
+            Topic\n\
+            [{topic}]\n\n\
+            \
+            Total Questions\n\
+            [{questions}]\n\n\
+            \
+            Right Answers\n\
+            [{answers}]\n\
+            ",
+            uid = log.uid,
+            subject = log.subject,
+            topic = log.topic_label(),
+            questions = log.total_questions,
+            answers = log.right_answers
+        ));
+    }
+
+    let buf = edit_in_tempfile(&note_builder_text)?;
+    logs_from_tf(buf, &journal.logs)
 }
 
-pub mod utils;
\ No newline at end of file
+pub mod cli;
+pub mod config;
+pub mod messages;
+pub mod utils;
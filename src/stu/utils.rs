@@ -1,48 +1,613 @@
-use colored::Colorize;
-use std::process::Command;
-use std::result::Result;
-use std::path::Path;
+use crate::stu::messages::{text, text_with};
+use crate::stu::StuError;
+use colored::{Color, Colorize};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::result::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Once, OnceLock};
+use terminal_size::{terminal_size_of, Width};
 
 pub fn get_date() -> String {
-    let date_process: std::process::Output;
-    if cfg!(windows) {
-        date_process = Command::new("cmd")
-        .args(["/C", "date /t"])
-        .output()
-        .expect("ERROR: Could no trun date process on windows");
+    let date_process: std::process::Output = if cfg!(windows) {
+        Command::new("cmd")
+            .args(["/C", "date /t"])
+            .output()
+            .expect("ERROR: Could no trun date process on windows")
     } else {
-        date_process = Command::new("/usr/bin/date")
-            .arg("+%m/%d/%Y")
+        Command::new("/usr/bin/date")
+            .arg("+%Y-%m-%d")
             .output()
-            .expect("ERROR: Could not run date process");
-
-    }
+            .expect("ERROR: Could not run date process")
+    };
     let output = std::str::from_utf8(&date_process.stdout)
-        .unwrap_or_else(|_| "unknown")
+        .unwrap_or("unknown")
         .trim();
-    return output.to_string();
+    if cfg!(windows) {
+        return normalize_date(output).unwrap_or_else(|| output.to_string());
+    }
+    output.to_string()
+}
+
+// `date /t` follows the locale on windows, e.g. `Mon 01/02/2006` or `02-01-2006`.
+// Slashes are read as month first unless that can't be a month, dashes and dots
+// as day first, and a leading year as `YYYY-MM-DD`.
+pub fn normalize_date(raw: &str) -> Option<String> {
+    let date = raw
+        .split_whitespace()
+        .find(|word| word.chars().any(|c| c.is_ascii_digit()))?;
+    let separator = date.chars().find(|c| matches!(c, '/' | '-' | '.'))?;
+    let parts: Vec<&str> = date.split(separator).collect();
+    let numbers: Vec<u32> = parts
+        .iter()
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+
+    let (year, month, day) = match numbers[..] {
+        [year, month, day] if parts[0].len() == 4 => (year, month, day),
+        [first, second, year] if separator == '/' && first <= 12 => (year, first, second),
+        [first, second, year] => (year, second, first),
+        _ => return None,
+    };
+    let year = if year < 100 { 2000 + year } else { year };
+
+    let (year, month, day) = checked_date(year as i64, month, day)?;
+    Some(canonical_date(year, month, day))
+}
+
+// Used when the output isn't a terminal, e.g. when piped into a file
+pub const DEFAULT_TERMINAL_WIDTH: usize = 120;
+
+pub fn terminal_width() -> Option<usize> {
+    if let Some((Width(width), _)) = terminal_size_of(std::io::stdout()) {
+        return Some(width as usize);
+    }
+
+    std::env::var("COLUMNS").ok()?.trim().parse().ok()
+}
+
+// How dates are shown and read from the command line, logs always store
+// them as `YYYY-MM-DD`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateFormat {
+    #[default]
+    Mdy,
+    Dmy,
+    Iso,
+}
+impl DateFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mdy" => Some(Self::Mdy),
+            "dmy" => Some(Self::Dmy),
+            "iso" => Some(Self::Iso),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Mdy => "mdy",
+            Self::Dmy => "dmy",
+            Self::Iso => "iso",
+        }
+    }
+
+    // How a date is written in this format, e.g. `MM/DD/YYYY`
+    pub fn pattern(self) -> &'static str {
+        match self {
+            Self::Mdy => text("date_pattern_mdy"),
+            Self::Dmy => text("date_pattern_dmy"),
+            Self::Iso => text("date_pattern_iso"),
+        }
+    }
+
+    pub fn format(self, (year, month, day): (i64, u32, u32)) -> String {
+        match self {
+            Self::Mdy => format!("{month:02}/{day:02}/{year:04}"),
+            Self::Dmy => format!("{day:02}/{month:02}/{year:04}"),
+            Self::Iso => canonical_date(year, month, day),
+        }
+    }
+
+    pub fn parse(self, date: &str) -> Option<(i64, u32, u32)> {
+        let separator = if self == Self::Iso { '-' } else { '/' };
+        let numbers: Vec<u32> = date
+            .trim()
+            .split(separator)
+            .map(|part| part.parse().ok())
+            .collect::<Option<_>>()?;
+
+        let (year, month, day) = match (self, &numbers[..]) {
+            (Self::Mdy, [month, day, year]) => (*year, *month, *day),
+            (Self::Dmy, [day, month, year]) => (*year, *month, *day),
+            (Self::Iso, [year, month, day]) => (*year, *month, *day),
+            _ => return None,
+        };
+        checked_date(year as i64, month, day)
+    }
+}
+
+// Set once from the config at startup, dates are shown as `MM/DD/YYYY` until then
+static DATE_FORMAT: OnceLock<DateFormat> = OnceLock::new();
+
+pub fn set_date_format(format: DateFormat) {
+    let _ = DATE_FORMAT.set(format);
+}
+
+pub fn date_format() -> DateFormat {
+    DATE_FORMAT.get().copied().unwrap_or_default()
+}
+
+// The form dates are stored in, which sorts and reads the same everywhere
+pub fn canonical_date(year: i64, month: u32, day: u32) -> String {
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// A stored date in the configured format, `unknown` and other unreadable
+// dates are shown as they are
+pub fn display_date(date: &str) -> String {
+    match parse_date(date) {
+        Some(date) => date_format().format(date),
+        None => date.to_string(),
+    }
+}
+
+// Parses a stored date into (year, month, day). Files from before data schema
+// 2 stored `MM/DD/YYYY`, which is still read
+pub fn parse_date(date: &str) -> Option<(i64, u32, u32)> {
+    DateFormat::Iso
+        .parse(date)
+        .or_else(|| DateFormat::Mdy.parse(date))
+}
+
+fn checked_date(year: i64, month: u32, day: u32) -> Option<(i64, u32, u32)> {
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    Some((year, month, day))
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+// The monday starting the week of the given day, 1970-01-01 was a thursday
+pub fn week_start(days: i64) -> i64 {
+    days - (days + 3).rem_euclid(7)
+}
+
+pub fn today() -> Option<i64> {
+    let (year, month, day) = parse_date(&get_date())?;
+    Some(days_from_civil(year, month, day))
+}
+
+// Days to wait before reviewing a log again, the worse it went the sooner
+pub fn review_interval_days(percentage: f32) -> i64 {
+    match percentage {
+        p if p < 50.0 => 1,
+        p if p < 70.0 => 3,
+        p if p < 90.0 => 7,
+        _ => 14,
+    }
+}
+
+pub const PERCENTAGE_BANDS: [&str; 4] = ["0-49%", "50-69%", "70-89%", "90-100%"];
+
+// Index into `PERCENTAGE_BANDS`, same cut points as the review intervals
+pub fn percentage_band(percentage: f32) -> usize {
+    match percentage {
+        p if p < 50.0 => 0,
+        p if p < 70.0 => 1,
+        p if p < 90.0 => 2,
+        _ => 3,
+    }
+}
+
+// Red at 0%, yellow at 50% and green at 100%, for `show --color-scale`
+pub fn scale_rgb(percentage: f32) -> (u8, u8, u8) {
+    let percentage = percentage.clamp(0.0, 100.0);
+    if percentage < 50.0 {
+        (255, (percentage / 50.0 * 255.0).round() as u8, 0)
+    } else {
+        (
+            (((100.0 - percentage) / 50.0) * 255.0).round() as u8,
+            255,
+            0,
+        )
+    }
+}
+
+// Terminals without 24-bit colors get one color per `PERCENTAGE_BANDS` band
+pub fn percentage_color(percentage: f32, truecolor: bool) -> Color {
+    if truecolor {
+        let (r, g, b) = scale_rgb(percentage);
+        return Color::TrueColor { r, g, b };
+    }
+
+    [Color::Red, Color::Yellow, Color::Green, Color::BrightGreen][percentage_band(percentage)]
+}
+
+// Set by terminals that can show 24-bit colors
+pub fn supports_truecolor() -> bool {
+    std::env::var("COLORTERM").is_ok_and(|x| matches!(x.as_str(), "truecolor" | "24bit"))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    #[default]
+    Nearest,
+    Floor,
+    Ceil,
+}
+impl RoundingMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "nearest" => Some(Self::Nearest),
+            "floor" => Some(Self::Floor),
+            "ceil" => Some(Self::Ceil),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Nearest => "nearest",
+            Self::Floor => "floor",
+            Self::Ceil => "ceil",
+        }
+    }
+
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            Self::Nearest => value.round(),
+            Self::Floor => value.floor(),
+            Self::Ceil => value.ceil(),
+        }
+    }
+}
+
+// Set once from the config at startup, `get_percentage` rounds to nearest
+// until then
+static ROUNDING_MODE: OnceLock<RoundingMode> = OnceLock::new();
+
+pub fn set_rounding_mode(mode: RoundingMode) {
+    let _ = ROUNDING_MODE.set(mode);
 }
 
 pub fn get_percentage(amount: f32, total: f32) -> f32 {
+    get_percentage_with(
+        amount,
+        total,
+        ROUNDING_MODE.get().copied().unwrap_or_default(),
+    )
+}
+
+// Nothing out of nothing is 0%, not NaN, which serde_json can't read back as a
+// number. Amounts over the total aren't capped and give more than 100%
+pub fn get_percentage_with(amount: f32, total: f32, mode: RoundingMode) -> f32 {
+    if total <= 0.0 {
+        return 0.0;
+    }
+
     let result = (amount * 100.0) / total;
-    let rounded = result.round();
-    return rounded;
+    mode.apply(result)
+}
+
+pub const PERCENTAGE_PRECISION: usize = 2;
+
+pub fn format_percentage(percentage: f32, precision: usize) -> String {
+    format!("{percentage:.precision$}%")
+}
+
+pub fn progress_bar(value: f32, goal: f32, width: usize) -> String {
+    let ratio = if goal <= 0.0 {
+        1.0
+    } else {
+        (value / goal).clamp(0.0, 1.0)
+    };
+    let filled = (ratio * width as f32).round() as usize;
+
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+const SPARK_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+// One bar per percentage, on a fixed 0 to 100 scale so journals can be told
+// apart at a glance instead of each one stretching to fill the bars
+pub fn sparkline(values: &[f32]) -> String {
+    values
+        .iter()
+        .map(|value| {
+            let level = (value.clamp(0.0, 100.0) / 100.0 * 7.0).round() as usize;
+            SPARK_BARS[level]
+        })
+        .collect()
+}
+
+// `edit` only fails once it tries to spawn the editor, so look for one first
+pub fn check_editor() -> Result<(), ()> {
+    edit::get_editor().map_err(|_| {
+        eprintln!("{}: {}", text("error").red(), text("no_editor"));
+    })?;
+
+    Ok(())
+}
+
+// CI runners and pipes have nobody to type into an editor, which would just
+// hang. `CI=false` or `CI=0` count as unset
+pub fn is_interactive(ci: Option<&str>, stdin_tty: bool, stdout_tty: bool) -> bool {
+    let in_ci = ci.is_some_and(|ci| !matches!(ci.trim(), "" | "0" | "false"));
+    !in_ci && stdin_tty && stdout_tty
+}
+
+pub fn check_interactive() -> Result<(), ()> {
+    use std::io::IsTerminal;
+
+    let ci = std::env::var("CI").ok();
+    if is_interactive(
+        ci.as_deref(),
+        std::io::stdin().is_terminal(),
+        std::io::stdout().is_terminal(),
+    ) {
+        return Ok(());
+    }
+
+    eprintln!("{}: {}", text("error").red(), text("non_interactive"));
+    Err(())
 }
 
-pub fn edit_text(filepath: String) -> Result<(), ()> {
+pub fn edit_text(filepath: &Path) -> Result<(), ()> {
     edit::edit_file(filepath).map_err(|err| {
-        eprintln!("{}: Could not edit file: {err}", "ERROR");
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("edit_file_failed", &[&err])
+        );
     })?;
 
     Ok(())
 }
+
+// Ctrl-C presses seen while at least one `InterruptGuard` was alive. Guards
+// compare against the count they started with, so they never reset it
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+static GUARDS: AtomicUsize = AtomicUsize::new(0);
+static HANDLER: Once = Once::new();
+
+// Without a guard alive Ctrl-C exits like it would without a handler
+fn on_interrupt() {
+    if GUARDS.load(Ordering::SeqCst) == 0 {
+        std::process::exit(130);
+    }
+    INTERRUPTS.fetch_add(1, Ordering::SeqCst);
+}
+
+// While alive, Ctrl-C no longer kills stu: the editor in the foreground still
+// gets it, and stu only takes note so the temp file is deleted once the editor
+// quits
+pub struct InterruptGuard {
+    start: usize,
+}
+
+impl InterruptGuard {
+    pub fn new() -> Self {
+        HANDLER.call_once(|| {
+            // Only fails if another handler was set, which stu never does
+            let _ = ctrlc::set_handler(on_interrupt);
+        });
+        GUARDS.fetch_add(1, Ordering::SeqCst);
+        Self {
+            start: INTERRUPTS.load(Ordering::SeqCst),
+        }
+    }
+
+    pub fn interrupted(&self) -> bool {
+        INTERRUPTS.load(Ordering::SeqCst) > self.start
+    }
+}
+
+impl Default for InterruptGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        GUARDS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn open_dir(path: &Path) -> Result<(), ()> {
+    open_with_system(path.as_os_str()).map_err(|err| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("open_dir_failed", &[&path.display(), &err])
+        );
+    })
+}
+
+// Opens the link in the default browser, see `is_url`
+pub fn open_url(url: &str) -> Result<(), ()> {
+    if !is_url(url) {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("invalid_resource_url", &[&url])
+        );
+        return Err(());
+    }
+
+    open_with_system(url.as_ref()).map_err(|err| {
+        eprintln!(
+            "{}: {}",
+            text("error").red(),
+            text_with("open_url_failed", &[&url, &err])
+        );
+    })
+}
+
+fn open_with_system(target: &std::ffi::OsStr) -> std::io::Result<()> {
+    let opener = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    // Not waited on, explorer exits with 1 even when it opened the directory
+    Command::new(opener).arg(target).spawn()?;
+
+    Ok(())
+}
+
+// Over SSH or without a display there is no clipboard to copy to. On X11 the
+// clipboard manager takes the content over when stu exits
+pub fn copy_to_clipboard(content: &str) -> Result<(), ()> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(content))
+        .map_err(|err| {
+            eprintln!(
+                "{}: {}",
+                text("error").red(),
+                text_with("no_clipboard", &[&err])
+            );
+        })
+}
+
+// Only http(s) links are handed to the opener, which would also run files
+// and programs
+pub fn is_url(value: &str) -> bool {
+    let rest = value
+        .strip_prefix("https://")
+        .or_else(|| value.strip_prefix("http://"));
+
+    rest.is_some_and(|rest| {
+        !rest.is_empty()
+            && !rest.starts_with('/')
+            && !rest.chars().any(|c| c.is_whitespace() || c.is_control())
+    })
+}
+
+// Cuts `value` down to `width` characters, ending in `...` when it was longer
+pub fn shorten(value: &str, width: usize) -> String {
+    if value.chars().count() <= width {
+        return value.to_string();
+    }
+
+    let kept: String = value.chars().take(width.saturating_sub(3)).collect();
+    format!("{kept}...")
+}
+
+// Asks a yes/no question on stdin, anything but a yes is a no
+pub fn confirm(question: &str) -> bool {
+    prompt(&format!("{question} [y/n]")).is_some_and(|answer| is_yes(&answer))
+}
+
+// The line typed after `question`, None when it couldn't be read
+pub fn prompt(question: &str) -> Option<String> {
+    print!("{question} ");
+    std::io::stdout().flush().ok()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok()?;
+
+    Some(answer)
+}
+
+pub fn is_yes(answer: &str) -> bool {
+    matches!(
+        answer.trim().to_lowercase().as_str(),
+        "y" | "yes" | "s" | "sim"
+    )
+}
+
+// The value typed between the template brackets, "[[x]]" and "[ x ]" both
+// give "x" while brackets inside the value are kept
 pub fn remove_brackets(string: &str) -> String {
-    string
-        .chars()
-        .filter(|c| c != &'[' && c != &']')
-        .collect::<String>()
+    let mut value = string.trim();
+
+    while let Some(inner) = value.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+        value = inner.trim();
+    }
+
+    // A bracket left alone after the other one was deleted
+    if !value.contains(']') {
+        value = value.trim_start_matches('[');
+    }
+    if !value.contains('[') {
+        value = value.trim_end_matches(']');
+    }
+
+    value.trim().to_string()
+}
+
+// The topics of `limits, derivatives`, blanks between the commas are dropped
+pub fn parse_topics(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|topic| topic.trim())
+        .filter(|topic| !topic.is_empty())
+        .map(|topic| topic.to_string())
+        .collect()
+}
+
+// A `subject,topic,questions,right` row, tab separated rows work as well
+pub fn parse_log_row(row: &str) -> Option<(String, String, usize, usize)> {
+    let separator = if row.contains('\t') { '\t' } else { ',' };
+    let fields: Vec<&str> = row.split(separator).map(|field| field.trim()).collect();
+
+    match fields[..] {
+        [subject, topic, questions, right] if !subject.is_empty() && !topic.is_empty() => {
+            let questions: usize = questions.parse().ok()?;
+            let right: usize = right.parse().ok()?;
+            if right > questions {
+                return None;
+            }
+            Some((subject.to_string(), topic.to_string(), questions, right))
+        }
+        _ => None,
+    }
 }
 
 pub fn is_string_numeric(str: &str) -> bool {
@@ -52,7 +617,7 @@ pub fn is_string_numeric(str: &str) -> bool {
         }
     }
 
-    return true;
+    true
 }
 
 pub fn is_string_alphanumeric(str: &str) -> bool {
@@ -61,52 +626,249 @@ pub fn is_string_alphanumeric(str: &str) -> bool {
             return false;
         }
     }
-    return true;
-}
-
-pub fn usage() {
-    println!("{usage}: stu <subcommand> <options>\n", usage = "Usage".red());
-    println!("Change editor with `EDITOR=emacs` for instance. Default editor is vim\n");
-    println!("{subcommands}:", subcommands = "Subcommands".red());
-    println!("    -h      --help                    print help");
-    println!();
-    println!("    show   <subcommand>               print all user journals, use -m if you wanna print the metrics");
-    println!("                ╰------------------------> print metrics: \"-m\"");
-    println!();
-    println!("    add    <subcommand> <value>       add either a new log or journal");
-    println!("                ╰------------------------> add journal: \"-j\"");
-    println!();
-    println!("    remove <subcommand> <value>       remove a log with the given <value>");
-    println!("                │          ╰-------------> value can be: [UID, journal]");
-    println!("                ╰------------------------> remove journal: \"-j\"");
-    println!();
-    println!("    get    <subcommand> <query>       search for <query> and print results");
-    println!("                │          ╰-------------> query can be: [UID, journal, subject, topic, \"MM/DD/YYYY\"]");
-    println!("                ╰------------------------> sort query: \"-s\"");
-    println!();
-    println!("    edit   <UID>                      edit log with the given UID");
-    println!();
-}
-
-pub fn setup_data() -> Result<String, ()> {
-    let home_path:String = simple_home_dir::home_dir().unwrap().display().to_string();
-    let data_dir_path = if cfg!(windows) { home_path + "\\stu\\" } else { "/local/share/stu/".to_string() };
-
-    if !std::path::Path::new(&data_dir_path).exists() {
-        std::fs::create_dir(&data_dir_path).map_err(|err| {
-            eprintln!("{}: Could not create database file: {err}", "ERROR");
-        }).unwrap();
-    }
-
-    let data_file_path = format!("{data_dir_path}data.json", );
-
-    if !Path::new(&data_file_path).exists() {
-        let mut file = File::create(&data_file_path).map_err(|err| {
-            eprintln!("{}: Could not create database file: {err}", "ERROR".red());
+    true
+}
+
+pub const COLOR_NAMES: &[&str] = &[
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+// colored's own `FromStr` falls back to white instead of failing on unknown names
+pub fn parse_color(name: &str) -> Option<Color> {
+    match name.trim().to_lowercase().as_str() {
+        "black" => Some(Color::Black),
+        "red" => Some(Color::Red),
+        "green" => Some(Color::Green),
+        "yellow" => Some(Color::Yellow),
+        "blue" => Some(Color::Blue),
+        "magenta" => Some(Color::Magenta),
+        "cyan" => Some(Color::Cyan),
+        "white" => Some(Color::White),
+        _ => None,
+    }
+}
+
+static SUBJECT_COLORS: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+pub fn set_subject_colors(colors: HashMap<String, String>) {
+    let _ = SUBJECT_COLORS.set(colors);
+}
+
+// The color configured for a subject, subjects are matched ignoring case
+pub fn subject_color(subject: &str) -> Option<Color> {
+    SUBJECT_COLORS
+        .get()?
+        .get(&subject.trim().to_lowercase())
+        .and_then(|color| parse_color(color))
+}
+
+pub const DEFAULT_RECENT_COUNT: usize = 10;
+
+pub const DEFAULT_WEAK_COUNT: usize = 5;
+
+pub const DEFAULT_PLAN_COUNT: usize = 5;
+
+pub const MAX_JOURNAL_NAME_LEN: usize = 64;
+
+// Resources are links, the column shows their start
+pub const RESOURCE_WIDTH: usize = 30;
+
+pub fn parse_goal(value: &str) -> Result<f32, StuError> {
+    match value.trim().trim_end_matches('%').parse::<f32>() {
+        Ok(goal) if (0.0..=100.0).contains(&goal) => Ok(goal),
+        _ => Err(StuError::new(text_with("invalid_goal", &[&value]))),
+    }
+}
+
+// A date given on the command line in the configured format, stored as
+// `YYYY-MM-DD` like the dates `get_date` returns
+pub fn parse_date_arg(value: &str) -> Result<String, StuError> {
+    let format = date_format();
+    match format.parse(value) {
+        Some((year, month, day)) => Ok(canonical_date(year, month, day)),
+        None => Err(StuError::new(text_with(
+            "invalid_date",
+            &[&value, &format.pattern()],
+        ))),
+    }
+}
+
+pub fn validate_journal_name(name: &str) -> Result<(), StuError> {
+    let error = if name.trim().is_empty() {
+        text("journal_name_empty").to_string()
+    } else if name.starts_with('-') {
+        text_with("journal_name_dash", &[&name])
+    } else if name.chars().any(|c| c.is_control()) {
+        text("journal_name_control").to_string()
+    } else if name.chars().count() > MAX_JOURNAL_NAME_LEN {
+        text_with("journal_name_too_long", &[&MAX_JOURNAL_NAME_LEN])
+    } else {
+        return Ok(());
+    };
+
+    Err(StuError::new(error))
+}
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const USAGE_KEYS: &[&str] = &[
+    "usage_help",
+    "usage_version",
+    "usage_lenient",
+    "usage_show",
+    "usage_add",
+    "usage_remove",
+    "usage_get",
+    "usage_edit",
+    "usage_last",
+    "usage_goal",
+    "usage_color",
+    "usage_alias",
+    "usage_merge",
+    "usage_merge_logs",
+    "usage_rename_subject",
+    "usage_purge",
+    "usage_archive",
+    "usage_stats",
+    "usage_review",
+    "usage_recent",
+    "usage_weak",
+    "usage_plan",
+    "usage_template",
+    "usage_config",
+    "usage_open",
+    "usage_diff",
+    "usage_export",
+];
+
+pub fn write_usage(out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "stu {VERSION}")?;
+    writeln!(
+        out,
+        "{usage}: stu <subcommand> <options>\n",
+        usage = text("usage_title").red()
+    )?;
+    writeln!(out, "{}\n", text("usage_editor"))?;
+    writeln!(
+        out,
+        "{subcommands}:",
+        subcommands = text("usage_subcommands_title").red()
+    )?;
+    for key in USAGE_KEYS {
+        writeln!(out, "{}", text(key))?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+// Asked for with `--help`, so it can be piped into a pager
+pub fn usage_stdout() {
+    let _ = write_usage(&mut std::io::stdout().lock());
+}
+
+// Printed next to an error, so it stays out of piped output
+pub fn usage_stderr() {
+    let _ = write_usage(&mut std::io::stderr().lock());
+}
+
+// `%APPDATA%\stu` on Windows, `$XDG_DATA_HOME/stu` or `~/.local/share/stu`
+// elsewhere. A directory left by older versions is used until it's moved
+pub fn data_dir() -> PathBuf {
+    let home = simple_home_dir::home_dir().unwrap();
+    let env = |name: &str| std::env::var(name).ok();
+    let dir = data_dir_for(cfg!(windows), &home, env("APPDATA"), env("XDG_DATA_HOME"));
+
+    let legacy = legacy_data_dir(cfg!(windows), &home);
+    if !dir.exists() && legacy.exists() {
+        legacy
+    } else {
+        dir
+    }
+}
+
+// `appdata` and `xdg_data_home` are the environment variables, a relative
+// `$XDG_DATA_HOME` is ignored as the spec asks
+pub fn data_dir_for(
+    windows: bool,
+    home: &Path,
+    appdata: Option<String>,
+    xdg_data_home: Option<String>,
+) -> PathBuf {
+    let base = if windows {
+        appdata
+            .filter(|x| !x.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join("AppData").join("Roaming"))
+    } else {
+        xdg_data_home
+            .map(PathBuf::from)
+            .filter(|x| x.is_absolute())
+            .unwrap_or_else(|| home.join(".local").join("share"))
+    };
+
+    base.join("stu")
+}
+
+// Where versions before `data_dir_for` kept their data
+pub fn legacy_data_dir(windows: bool, home: &Path) -> PathBuf {
+    if windows {
+        home.join("stu")
+    } else {
+        PathBuf::from("/local/share/stu")
+    }
+}
+
+// The directory of the data file, where the files that go with it are kept
+pub fn data_dir_of(data_file: &Path) -> &Path {
+    data_file
+        .parent()
+        .filter(|path| !path.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+pub fn template_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("template.txt")
+}
+
+pub fn draft_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("draft.txt")
+}
+
+pub fn setup_data(data_file_path: impl AsRef<Path>) -> Result<PathBuf, ()> {
+    let data_file_path = data_file_path.as_ref();
+    let data_dir_path = data_file_path
+        .parent()
+        .filter(|path| !path.as_os_str().is_empty());
+
+    if let Some(data_dir_path) = data_dir_path {
+        if !data_dir_path.exists() {
+            std::fs::create_dir_all(data_dir_path).map_err(|err| {
+                eprintln!(
+                    "{}: {}",
+                    text("error").red(),
+                    text_with("create_database_failed", &[&err])
+                );
+            })?;
+        }
+    }
+
+    if !data_file_path.exists() {
+        let mut file = File::create(data_file_path).map_err(|err| {
+            eprintln!(
+                "{}: {}",
+                text("error").red(),
+                text_with("create_database_failed", &[&err])
+            );
         })?;
 
-        writeln!(file, "[\n]").unwrap();
+        writeln!(
+            file,
+            "{{\"schema\":{},\"journals\":[]}}",
+            crate::stu::SCHEMA_VERSION
+        )
+        .unwrap();
     }
 
-    Ok(data_file_path)
+    Ok(data_file_path.to_path_buf())
 }
@@ -1,22 +1,46 @@
+#![allow(clippy::result_unit_err)]
+
 #[cfg(test)]
 mod tests {
-    use crate::stu::utils;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::path::{Path, PathBuf};
+    use crate::stu::{self, cli::{self, AliasAction, Command, LogSource, Query, CopyFormat, ShowOptions}, config::{Config, TagRules}, messages, utils, TableFormat, TableStyle};
 
     #[test]
     fn test_get_date() {
         utils::get_date();
         let result = utils::get_date();
-        let expected: String;
-        if cfg!(windows) {
+        let expected: String = if cfg!(windows) {
             let date = std::process::Command::new("cmd").args(["/C", "date /t"]).output().expect("Could not use date on tests");
-            expected = std::str::from_utf8(&date.stdout).unwrap_or_else(|_| "unknown").trim().to_string();
+            let date = std::str::from_utf8(&date.stdout).unwrap_or("unknown").trim();
+            utils::normalize_date(date).unwrap_or_else(|| date.to_string())
         } else {
-            let date = std::process::Command::new("/usr/bin/date").arg("+%m/%d/%Y").output().expect("ERROR: Could not run date process");
-            expected = std::str::from_utf8(&date.stdout).unwrap_or_else(|_| "unknown").trim().to_string();
-        }
+            let date = std::process::Command::new("/usr/bin/date").arg("+%Y-%m-%d").output().expect("ERROR: Could not run date process");
+            std::str::from_utf8(&date.stdout).unwrap_or("unknown").trim().to_string()
+        };
         assert_eq!(result, expected);
     }
 
+    #[test]
+    #[cfg(windows)]
+    fn test_get_date_format_on_windows() {
+        assert!(utils::parse_date(&utils::get_date()).is_some());
+    }
+
+    #[test]
+    fn test_normalize_date() {
+        assert_eq!(utils::normalize_date("Mon 01/02/2006").as_deref(), Some("2006-01-02"));
+        assert_eq!(utils::normalize_date("02-01-2006").as_deref(), Some("2006-01-02"));
+        assert_eq!(utils::normalize_date("15.10.2026").as_deref(), Some("2026-10-15"));
+        assert_eq!(utils::normalize_date("15/10/2026").as_deref(), Some("2026-10-15"));
+        assert_eq!(utils::normalize_date("2026-10-15").as_deref(), Some("2026-10-15"));
+        assert_eq!(utils::normalize_date("10/15/26").as_deref(), Some("2026-10-15"));
+        assert_eq!(utils::normalize_date("10/15/2026").as_deref(), Some("2026-10-15"));
+        assert_eq!(utils::normalize_date("unknown"), None);
+        assert_eq!(utils::normalize_date("13/13/2026"), None);
+    }
+
     #[test]
     fn test_edit_text() {
         let mut tf = tempfile::Builder::new()
@@ -32,7 +56,7 @@ mod tests {
         write!(tf, "{}", &note_builder_text).unwrap();
         tf.flush().unwrap();
 
-        utils::edit_text(tf.path().display().to_string()).unwrap();
+        utils::edit_text(tf.path()).unwrap();
 
         tf.flush().unwrap();
         tf.rewind().unwrap();
@@ -47,9 +71,1084 @@ mod tests {
 
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_interrupt_guard() {
+        // The guard stays alive until the handler ran, so the signal never
+        // reaches a process without one
+        let guard = utils::InterruptGuard::new();
+        assert!(!guard.interrupted());
+        unsafe { libc::raise(libc::SIGINT) };
+        for _ in 0..100 {
+            if guard.interrupted() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(guard.interrupted());
+        assert!(!utils::InterruptGuard::new().interrupted());
+    }
+
+    #[test]
+    fn test_validate_journal_name() {
+        assert!(utils::validate_journal_name("math").is_ok());
+        assert!(utils::validate_journal_name("linear algebra").is_ok());
+
+        assert!(utils::validate_journal_name("").is_err());
+        assert!(utils::validate_journal_name("   ").is_err());
+        assert!(utils::validate_journal_name("-j").is_err());
+        assert!(utils::validate_journal_name("math\nbio").is_err());
+        assert!(utils::validate_journal_name("math\t").is_err());
+        assert!(utils::validate_journal_name(&"a".repeat(utils::MAX_JOURNAL_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_data_dir_for() {
+        use std::path::{Path, PathBuf};
+        let home = Path::new("/home/ana");
+        assert_eq!(utils::data_dir_for(false, home, None, None), PathBuf::from("/home/ana/.local/share/stu"));
+        assert_eq!(utils::data_dir_for(false, home, None, Some("/data".to_string())), PathBuf::from("/data/stu"));
+        assert_eq!(utils::data_dir_for(false, home, None, Some("data".to_string())), PathBuf::from("/home/ana/.local/share/stu"));
+        assert_eq!(utils::data_dir_for(true, home, Some("/appdata".to_string()), None), Path::new("/appdata").join("stu"));
+        assert_eq!(utils::data_dir_for(true, home, None, Some("/data".to_string())), home.join("AppData").join("Roaming").join("stu"));
+        assert_eq!(utils::legacy_data_dir(true, home), home.join("stu"));
+        assert_eq!(utils::template_path(Path::new("/data/stu")), Path::new("/data/stu/template.txt"));
+        assert_eq!(utils::draft_path(utils::data_dir_of(Path::new("/data/stu/data.json"))), Path::new("/data/stu/draft.txt"));
+        assert_eq!(utils::data_dir_of(Path::new("data.json")), Path::new("."));
+    }
+
+    #[test]
+    fn test_get_percentage_rounding() {
+        use utils::RoundingMode;
+        assert_eq!(utils::get_percentage_with(79.5, 100.0, RoundingMode::Nearest), 80.0);
+        assert_eq!(utils::get_percentage_with(79.5, 100.0, RoundingMode::Floor), 79.0);
+        assert_eq!(utils::get_percentage_with(79.5, 100.0, RoundingMode::Ceil), 80.0);
+        assert_eq!(utils::get_percentage_with(79.4, 100.0, RoundingMode::Ceil), 80.0);
+        assert_eq!(utils::get_percentage_with(2.0, 3.0, RoundingMode::Nearest), 67.0);
+        assert_eq!(utils::get_percentage_with(2.0, 3.0, RoundingMode::Floor), 66.0);
+        assert_eq!(RoundingMode::from_name("floor"), Some(RoundingMode::Floor));
+        assert_eq!(RoundingMode::from_name("down"), None);
+        assert_eq!(utils::get_percentage(79.5, 100.0), 80.0);
+    }
+
+    #[test]
+    fn test_get_percentage_edge_cases() {
+        use utils::RoundingMode;
+        // f32::round goes away from zero on exact halves, 12.5 is 13 and not the even 12
+        assert_eq!(utils::get_percentage_with(1.0, 8.0, RoundingMode::Nearest), 13.0);
+        assert_eq!(utils::get_percentage_with(3.0, 8.0, RoundingMode::Nearest), 38.0);
+        assert_eq!(utils::get_percentage_with(1.0, 8.0, RoundingMode::Floor), 12.0);
+        assert_eq!(utils::get_percentage_with(0.0, 0.0, RoundingMode::Nearest), 0.0);
+        assert_eq!(utils::get_percentage_with(5.0, 0.0, RoundingMode::Ceil), 0.0);
+        assert_eq!(utils::get_percentage_with(3.0, 2.0, RoundingMode::Nearest), 150.0);
+        assert_eq!(utils::get_percentage_with(0.0, 7.0, RoundingMode::Ceil), 0.0);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(utils::sparkline(&[0.0, 50.0, 100.0]), "▁▅█");
+        assert_eq!(utils::sparkline(&[-5.0, 14.0, 150.0]), "▁▂█");
+        assert_eq!(utils::sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_format_percentage() {
+        assert_eq!(utils::format_percentage(80.0, 2), "80.00%");
+        assert_eq!(utils::format_percentage(66.666, 2), "66.67%");
+        assert_eq!(utils::format_percentage(0.0, 0), "0%");
+        assert_eq!(utils::format_percentage(100.0, 1), "100.0%");
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(utils::progress_bar(0.0, 90.0, 10), "[----------]");
+        assert_eq!(utils::progress_bar(45.0, 90.0, 10), "[#####-----]");
+        assert_eq!(utils::progress_bar(95.0, 90.0, 10), "[##########]");
+        assert_eq!(utils::progress_bar(50.0, 0.0, 4), "[####]");
+    }
+
+    #[test]
+    fn test_parse_goal() {
+        assert_eq!(utils::parse_goal("90"), Ok(90.0));
+        assert_eq!(utils::parse_goal("72.5%"), Ok(72.5));
+        assert!(utils::parse_goal("101").is_err());
+        assert!(utils::parse_goal("-1").is_err());
+        assert!(utils::parse_goal("ninety").is_err());
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(utils::parse_color("red"), Some(colored::Color::Red));
+        assert_eq!(utils::parse_color(" Blue "), Some(colored::Color::Blue));
+        assert_eq!(utils::parse_color("rainbow"), None);
+        for name in utils::COLOR_NAMES {
+            assert!(utils::parse_color(name).is_some());
+        }
+    }
+
+    #[test]
+    fn test_remove_brackets() {
+        assert_eq!(utils::remove_brackets("[Algebra]"), "Algebra");
+        assert_eq!(utils::remove_brackets("[  Algebra  ]"), "Algebra");
+        assert_eq!(utils::remove_brackets("  Algebra  "), "Algebra");
+        assert_eq!(utils::remove_brackets("[]"), "");
+        assert_eq!(utils::remove_brackets("[   ]"), "");
+        assert_eq!(utils::remove_brackets("[[x]]"), "x");
+        assert_eq!(utils::remove_brackets("[ [ x ] ]"), "x");
+        assert_eq!(utils::remove_brackets("[Chapter [3]]"), "Chapter [3]");
+        assert_eq!(utils::remove_brackets("[Algebra"), "Algebra");
+        assert_eq!(utils::remove_brackets("Algebra]"), "Algebra");
+        assert_eq!(utils::remove_brackets("[20]"), "20");
+    }
+
+    #[test]
+    fn test_parse_log_row() {
+        let expected = Some(("math".to_string(), "fractions".to_string(), 20, 15));
+        assert_eq!(utils::parse_log_row("math,fractions,20,15"), expected);
+        assert_eq!(utils::parse_log_row(" math\t fractions \t20\t15"), expected);
+        assert_eq!(utils::parse_log_row("math,fractions,20"), None);
+        assert_eq!(utils::parse_log_row("math,fractions,twenty,15"), None);
+        assert_eq!(utils::parse_log_row("math,fractions,10,15"), None);
+        assert_eq!(utils::parse_log_row(",fractions,20,15"), None);
+    }
+
+    #[test]
+    fn test_is_yes() {
+        assert!(utils::is_yes("y\n"));
+        assert!(utils::is_yes(" Yes "));
+        assert!(utils::is_yes("sim"));
+        assert!(!utils::is_yes("\n"));
+        assert!(!utils::is_yes("n"));
+        assert!(!utils::is_yes("yep"));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(utils::parse_date("10/15/2026"), Some((2026, 10, 15)));
+        assert_eq!(utils::parse_date("02/29/2024"), Some((2024, 2, 29)));
+        assert_eq!(utils::parse_date("02/29/2023"), None);
+        assert_eq!(utils::parse_date("13/01/2026"), None);
+        assert_eq!(utils::parse_date("unknown"), None);
+        assert_eq!(utils::parse_date("2026-10-15"), Some((2026, 10, 15)));
+        assert_eq!(utils::parse_date("2023-02-29"), None);
+    }
+
+    #[test]
+    fn test_date_formats() {
+        use utils::DateFormat;
+
+        assert_eq!(DateFormat::Dmy.parse("15/10/2026"), Some((2026, 10, 15)));
+        assert_eq!(DateFormat::Dmy.parse("10/15/2026"), None);
+        assert_eq!(DateFormat::Iso.parse("2026-10-15"), Some((2026, 10, 15)));
+        assert_eq!(DateFormat::Iso.parse("10/15/2026"), None);
+        assert_eq!(DateFormat::Mdy.parse("2026-10-15"), None);
+        for format in [DateFormat::Mdy, DateFormat::Dmy, DateFormat::Iso] {
+            assert_eq!(format.parse(&format.format((2026, 3, 7))), Some((2026, 3, 7)));
+            assert_eq!(DateFormat::from_name(format.name()), Some(format));
+        }
+        assert_eq!(DateFormat::Dmy.format((2026, 3, 7)), "07/03/2026");
+        assert_eq!(DateFormat::from_name("ymd"), None);
+        assert_eq!(Config::parse("date_format = \"iso\"\n").unwrap().date_format, DateFormat::Iso);
+        assert!(Config::parse("date_format = \"ymd\"\n").is_err());
+    }
+
+    #[test]
+    fn test_civil_days_roundtrip() {
+        assert_eq!(utils::days_from_civil(1970, 1, 1), 0);
+        assert_eq!(utils::days_from_civil(2000, 3, 1), 11017);
+        for days in [-1000, 0, 11017, 20000, 20741] {
+            let (year, month, day) = utils::civil_from_days(days);
+            assert_eq!(utils::days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn test_week_start() {
+        // 10/15/2026 is a thursday, its week starts on monday 10/12/2026
+        let days = utils::days_from_civil(2026, 10, 15);
+        assert_eq!(utils::civil_from_days(utils::week_start(days)), (2026, 10, 12));
+        let monday = utils::days_from_civil(2026, 10, 12);
+        assert_eq!(utils::week_start(monday), monday);
+    }
+
+    #[test]
+    fn test_percentage_band() {
+        assert_eq!(utils::percentage_band(0.0), 0);
+        assert_eq!(utils::percentage_band(49.0), 0);
+        assert_eq!(utils::percentage_band(50.0), 1);
+        assert_eq!(utils::percentage_band(89.0), 2);
+        assert_eq!(utils::percentage_band(90.0), 3);
+        assert_eq!(utils::percentage_band(100.0), 3);
+    }
+
+    #[test]
+    fn test_review_interval_days() {
+        assert_eq!(utils::review_interval_days(0.0), 1);
+        assert_eq!(utils::review_interval_days(49.0), 1);
+        assert_eq!(utils::review_interval_days(50.0), 3);
+        assert_eq!(utils::review_interval_days(75.0), 7);
+        assert_eq!(utils::review_interval_days(100.0), 14);
+    }
+
+    #[test]
+    fn test_config_parse() {
+        let content = "# stu defaults\neditor = \"nvim\"\nsort = true\n\ncolor = false\ndata_path = \"/tmp/stu \\\"x\\\".json\"\n";
+        let config = Config::parse(content).unwrap();
+        assert_eq!(config.editor.as_deref(), Some("nvim"));
+        assert!(config.sort);
+        assert!(!config.color);
+        assert_eq!(config.data_path, PathBuf::from("/tmp/stu \"x\".json"));
+
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert!(Config::parse("sort = yes").is_err());
+        assert!(Config::parse("editor = nvim").is_err());
+        assert!(Config::parse("theme = \"dark\"").is_err());
+        assert!(Config::parse("[stu]").is_err());
+        assert_eq!(Config::parse("round = \"floor\"").unwrap().round, utils::RoundingMode::Floor);
+        assert!(Config::parse("round = \"down\"").is_err());
+        assert_eq!(Config::parse("default_journal = \"math\"").unwrap().default_journal.as_deref(), Some("math"));
+        assert!(Config::parse("trust_stored_percentage = true").unwrap().trust_stored_percentage);
+        assert!(Config::parse("trust_stored_percentage = \"yes\"").is_err());
+        assert!(Config::parse("open_after_add = true").unwrap().open_after_add);
+
+        let config = Config::parse("sort = true\n[subject_colors]\nAlgebra = \"Red\"\n\"linear algebra\" = \"blue\"\n").unwrap();
+        assert!(config.sort);
+        assert_eq!(config.subject_colors.get("algebra").map(String::as_str), Some("red"));
+        assert_eq!(config.subject_colors.get("linear algebra").map(String::as_str), Some("blue"));
+        assert!(Config::parse("[subject_colors]\nalgebra = \"pink\"").is_err());
+        assert!(Config::parse("[subject_colors]\nalgebra = red").is_err());
+
+        let config = Config::parse("sort = true  # newest first is the default\neditor = 'nvim'\nsubject_colors = { Algebra = 'red' }\n").unwrap();
+        assert!(config.sort);
+        assert_eq!(config.editor.as_deref(), Some("nvim"));
+        assert_eq!(config.subject_colors.get("algebra").map(String::as_str), Some("red"));
+    }
+
+    #[test]
+    fn test_tag_rules() {
+        let rules = TagRules::parse("# keyword = \"tag\"\nfraction = \"math-basics\"\n\"Linear algebra\" = \"math\"\nalgebra = \"math\"\n").unwrap();
+        assert_eq!(rules.tags_for(&["algebra", "fractions"]), vec!["math-basics", "math"]);
+        assert_eq!(rules.tags_for(&["LINEAR ALGEBRA", "matrices"]), vec!["math"]);
+        assert!(rules.tags_for(&["biology", "cells"]).is_empty());
+
+        assert_eq!(TagRules::parse("").unwrap(), TagRules::default());
+        assert!(TagRules::parse("fraction = math").is_err());
+        assert!(TagRules::parse("fraction").is_err());
+        assert!(TagRules::parse("fraction = \"\"").is_err());
+        assert!(TagRules::parse("fraction = 1").is_err());
+        assert_eq!(TagRules::parse("fraction = 'math' # basics").unwrap().tags_for(&["fractions"]), vec!["math"]);
+    }
+
+    #[test]
+    fn test_write_usage() {
+        let mut out: Vec<u8> = Vec::new();
+        utils::write_usage(&mut out).unwrap();
+        let usage = String::from_utf8(out).unwrap();
+        assert!(usage.starts_with(&format!("stu {}\n", utils::VERSION)));
+        assert!(usage.contains(messages::text("usage_help")));
+    }
+
+    #[test]
+    fn test_success_messages_spelling() {
+        let keys = ["created_journal", "added_log", "imported_logs", "exported", "removed_journal", "pruned_journals", "removed_log", "edited_log", "merged_journals", "merged_logs", "wrote_output", "purged", "renamed", "archived_log", "unarchived_log"];
+        for key in keys {
+            assert!(messages::text_in("en", key).starts_with("Successfully"));
+        }
+    }
+
+    #[test]
+    fn test_text_language_fallback() {
+        assert_eq!(messages::text_in("pt_BR.UTF-8", "header_subject"), "Matéria");
+        assert_eq!(messages::text_in("xx", "header_subject"), "Subject");
+        assert_eq!(messages::text_in("en", "missing_key"), "missing_key");
+    }
+
+    #[test]
+    fn test_missing_date_filter() {
+        let filter = stu::LogFilter { missing_date: true, ..Default::default() };
+        let mut log = stu::Log::new();
+        assert_eq!(log.date, stu::UNKNOWN_DATE);
+        assert!(filter.matches(&log));
+        log.date = "10/01/2026".to_string();
+        assert!(!filter.matches(&log));
+        assert!(stu::LogFilter::default().matches(&log));
+
+        let day = log.day().unwrap();
+        let filter = stu::LogFilter { since: Some(day), ..Default::default() };
+        assert!(filter.matches(&log));
+        log.date = "09/30/2026".to_string();
+        assert!(!filter.matches(&log));
+    }
+
+    #[test]
+    fn test_sort_key() {
+        use std::cmp::Ordering;
+        use stu::{SortField, SortKey};
+
+        assert_eq!(SortKey::parse("topic"), Some(SortKey { field: SortField::Topic, descending: false }));
+        assert_eq!(SortKey::parse("Right:desc"), Some(SortKey { field: SortField::Right, descending: true }));
+        assert_eq!(SortKey::parse("date:up"), None);
+        assert_eq!(SortKey::parse("grade"), None);
+
+        let mut a = stu::Log::new();
+        a.subject = "algebra".to_string();
+        a.date = "12/01/2025".to_string();
+        a.percentage = 90.0;
+        let mut b = stu::Log::new();
+        b.subject = "Biology".to_string();
+        b.date = "01/05/2026".to_string();
+        b.percentage = 50.0;
+        assert_eq!(SortKey::parse("subject").unwrap().compare(&a, &b), Ordering::Less);
+        assert_eq!(SortKey::parse("date:desc").unwrap().compare(&a, &b), Ordering::Greater);
+        assert_eq!(SortKey::parse("percentage").unwrap().compare(&a, &b), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_sort_journals() {
+        use stu::{Journal, JournalSort};
+
+        let mut small = Journal::new("physics");
+        let mut log = stu::Log::new();
+        log.total_questions = 10;
+        log.right_answers = 9;
+        small.add_log(log);
+        let mut big = Journal::new("Biology");
+        for _ in 0..2 {
+            let mut log = stu::Log::new();
+            log.total_questions = 10;
+            log.right_answers = 5;
+            big.add_log(log);
+        }
+        let mut journals = vec![small, big, Journal::new("art")];
+        let names = |journals: &[Journal]| journals.iter().map(|x| x.name.clone()).collect::<Vec<_>>();
+
+        stu::sort_journals(&mut journals, JournalSort::Name);
+        assert_eq!(names(&journals), ["art", "Biology", "physics"]);
+        stu::sort_journals(&mut journals, JournalSort::Logs);
+        assert_eq!(names(&journals), ["Biology", "physics", "art"]);
+        stu::sort_journals(&mut journals, JournalSort::Percentage);
+        assert_eq!(names(&journals), ["physics", "Biology", "art"]);
+        assert_eq!(JournalSort::from_name("size"), None);
+        assert!(parse(&["show", "--sort-journals", "size"]).is_err());
+    }
+
+    #[test]
+    fn test_scale_rgb() {
+        use colored::Color;
+
+        assert_eq!(utils::scale_rgb(0.0), (255, 0, 0));
+        assert_eq!(utils::scale_rgb(25.0), (255, 128, 0));
+        assert_eq!(utils::scale_rgb(50.0), (255, 255, 0));
+        assert_eq!(utils::scale_rgb(75.0), (128, 255, 0));
+        assert_eq!(utils::scale_rgb(100.0), (0, 255, 0));
+        assert_eq!(utils::scale_rgb(-5.0), (255, 0, 0));
+        assert_eq!(utils::scale_rgb(120.0), (0, 255, 0));
+        assert_eq!(utils::percentage_color(75.0, true), Color::TrueColor { r: 128, g: 255, b: 0 });
+        assert_eq!(utils::percentage_color(75.0, false), Color::Green);
+        assert_eq!(utils::percentage_color(40.0, false), Color::Red);
+    }
+
+    #[test]
+    fn test_show_metrics_mean() {
+        let mut journal = stu::Journal::new("math");
+        for (questions, right) in [(100, 90), (10, 1)] {
+            let mut log = stu::Log::new();
+            log.total_questions = questions;
+            log.right_answers = right;
+            log.percentage = utils::get_percentage(right as f32, questions as f32);
+            journal.add_log(log);
+        }
+        assert_eq!(stu::simple_mean(&journal), 50.0);
+        assert_eq!(stu::simple_mean(&stu::Journal::new("bio")), 0.0);
+
+        colored::control::set_override(false);
+        let render = |mean| {
+            let mut out = Vec::new();
+            stu::show_metrics_compact(&mut out, std::slice::from_ref(&journal), TableStyle::Ascii, mean).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert!(render(stu::Mean::Weighted).contains("83.00%"));
+        assert!(render(stu::Mean::Simple).contains("50.00%"));
+
+        assert!(matches!(parse(&["show", "-m", "--mean", "simple"]), Ok(Command::Show(options)) if options.mean == Some(stu::Mean::Simple)));
+        assert!(parse(&["show", "--mean", "simple"]).is_err());
+        assert!(parse(&["show", "-m", "--mean", "median"]).is_err());
+    }
+
+    #[test]
+    fn test_backup_data() {
+        let (dir, path) = sample_data_file();
+
+        let backup = stu::backup_data(&path, "purge-1").unwrap();
+        assert_eq!(backup, dir.path().join("data.json.purge-1.bak"));
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), SAMPLE_DATA);
+        assert!(stu::backup_data(dir.path().join("missing.json"), "x").is_err());
+
+        assert_eq!(parse(&["purge"]), Ok(Command::Purge));
+        assert!(parse(&["purge", "--force"]).is_err());
+    }
+
+    #[test]
+    fn test_log_resource() {
+        let log = stu::log_from_tf(filled_template("20", "15") + "\nResource (URL)\n[https://example.com/fractions]\n").unwrap();
+        assert_eq!(log.resource.as_deref(), Some("https://example.com/fractions"));
+        let log = stu::log_from_tf(filled_template("20", "15") + "\nResource (URL)\n[]\n").unwrap();
+        assert_eq!(log.resource, None);
+        assert_eq!(stu::log_from_tf(filled_template("20", "15")).unwrap().resource, None);
+
+        assert!(utils::is_url("https://example.com/a?b=c"));
+        assert!(utils::is_url("http://localhost:8080"));
+        assert!(!utils::is_url("example.com"));
+        assert!(!utils::is_url("file:///etc/passwd"));
+        assert!(!utils::is_url("https://"));
+        assert!(!utils::is_url("https://example.com/a b"));
+        assert_eq!(utils::shorten("https://example.com", 30), "https://example.com");
+        assert_eq!(utils::shorten("https://example.com/fractions", 15), "https://exam...");
+
+        assert_eq!(parse(&["open", "111", "--resource"]), Ok(Command::OpenResource("111".to_string())));
+        assert!(parse(&["open", "111"]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_uid_prefix() {
+        let mut journal = stu::Journal::new("math");
+        for uid in ["123", "1234", "456789", "456123"] {
+            let mut log = stu::Log::new();
+            log.uid = uid.to_string();
+            journal.add_log(log);
+        }
+        let journals = [journal];
+
+        assert_eq!(stu::resolve_uid_prefix(&journals, "4567"), Ok("456789".to_string()));
+        assert_eq!(stu::resolve_uid_prefix(&journals, "123"), Ok("123".to_string()));
+        assert_eq!(stu::resolve_uid_prefix(&journals, "1234"), Ok("1234".to_string()));
+        assert!(stu::resolve_uid_prefix(&journals, "456").is_err());
+        assert!(stu::resolve_uid_prefix(&journals, "9").is_err());
+    }
+
+    #[test]
+    fn test_run_uid_prefix() {
+        let (_dir, path) = sample_data_file();
+
+        run(&["edit", "22", "--append-note", "short uid"], &path).unwrap();
+        assert_eq!(read_data(&path)[0]["logs"][1]["notes"].as_str().map(|x| x.ends_with("short uid")), Some(true));
+        run(&["remove", "11"], &path).unwrap();
+        assert_eq!(read_data(&path)[0]["logs"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_is_interactive() {
+        assert!(utils::is_interactive(None, true, true));
+        assert!(!utils::is_interactive(None, false, true));
+        assert!(!utils::is_interactive(None, true, false));
+        assert!(!utils::is_interactive(Some("true"), true, true));
+        assert!(utils::is_interactive(Some("false"), true, true));
+        assert!(utils::is_interactive(Some(""), true, true));
+    }
+
+    #[test]
+    fn test_porcelain_line() {
+        let mut log = stu::Log::new();
+        log.uid = "111".to_string();
+        log.date = "10/01/2026".to_string();
+        log.subject = "linear\talgebra".to_string();
+        log.topics = vec!["fractions".to_string(), "ratios".to_string()];
+        log.total_questions = 20;
+        log.right_answers = 15;
+        log.percentage = 75.0;
+        assert_eq!(stu::porcelain_line("math", &log), "math\t111\t10/01/2026\tlinear algebra\tfractions, ratios\t20\t15\t75.00");
+    }
+
+    fn filled_template(questions: &str, answers: &str) -> String {
+        format!("Subject\n[algebra]\n\nTopic\n[fractions]\n\nTotal Questions\n[{questions}]\n\nRight Answers\n[{answers}]\n")
+    }
+
+    #[test]
+    fn test_log_from_tf_whole_numbers() {
+        let log = stu::log_from_tf(filled_template("20", "15")).unwrap();
+        assert_eq!((log.total_questions, log.right_answers), (20, 15));
+
+        assert!(stu::log_from_tf(filled_template("20.5", "15")).is_err());
+        assert!(stu::log_from_tf(filled_template("20", "15.0")).is_err());
+        assert!(stu::log_from_tf(filled_template("twenty", "15")).is_err());
+        assert_eq!(messages::text_in("en", "not_whole_number").replacen("{}", "Total Questions", 1).replacen("{}", "20.5", 1), "Total Questions must be a whole number, got '20.5'");
+    }
+
+    // Counts the allocations of each thread, so a test can check what a call
+    // allocates while other tests run alongside
+    struct CountingAlloc;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAlloc = CountingAlloc;
+
+    const SAMPLE_DATA: &str = r#"[{"name":"math","logs":[{"subject":"algebra","topic":"fractions","date":"10/01/2026","uid":"111","total_questions":20,"right_answers":15,"percentage":0.0},{"subject":"geometry","topic":"triangles","date":"10/02/2026","uid":"222","total_questions":10,"right_answers":9,"percentage":0.0}]},{"name":"bio","logs":[]}]"#;
+
+    // A data file with SAMPLE_DATA in a fresh temporary directory
+    fn sample_data_file() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        std::fs::write(&path, SAMPLE_DATA).unwrap();
+        (dir, path)
+    }
+
+    fn run(args: &[&str], path: &Path) -> Result<(), ()> {
+        let config = Config { data_path: path.to_path_buf(), ..Config::default() };
+        cli::run(args.iter().map(|x| x.to_string()).collect(), &config, path)
+    }
+
+    // The journals array of a data file
+    fn read_data(path: &Path) -> serde_json::Value {
+        let data: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(data["schema"], stu::SCHEMA_VERSION);
+        data["journals"].clone()
+    }
+
+    fn parse(args: &[&str]) -> Result<Command, stu::StuError> {
+        cli::parse_args(args.iter().map(|x| x.to_string()).collect())
+    }
+
+    #[test]
+    fn test_parse_args_error() {
+        let err = parse(&["stats", "--by-topic"]).unwrap_err();
+        assert_eq!(err.message, messages::text("by_topic_needs_journal"));
+        assert!(!err.usage);
+        assert!(parse(&[]).unwrap_err().usage);
+    }
+
+    #[test]
+    fn test_parse_args_strict() {
+        assert!(parse(&["last", "extra"]).is_err());
+        assert!(parse(&["goal", "math", "80", "90"]).is_err());
+        assert!(parse(&["get", "--tag", "exam", "extra"]).is_err());
+        assert!(parse(&["get", "111", "--edit", "extra"]).is_err());
+        assert!(parse(&["template"]).is_err());
+        assert!(parse(&["alias", "math"]).is_err());
+        assert_eq!(parse(&["--lenient", "last", "extra"]), Ok(Command::Last));
+        assert_eq!(parse(&["--lenient", "get", "111", "--edit", "extra"]), Ok(Command::Get(Query::Uid { uid: "111".to_string(), edit: true, copy: None })));
+        assert!(parse(&["last", "--lenient"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&["get", "123"]), Ok(Command::Get(Query::Uid { uid: "123".to_string(), edit: false, copy: None })));
+        assert_eq!(parse(&["get", "123", "--edit"]), Ok(Command::Get(Query::Uid { uid: "123".to_string(), edit: true, copy: None })));
+        assert_eq!(parse(&["get", "123", "--copy-json"]), Ok(Command::Get(Query::Uid { uid: "123".to_string(), edit: false, copy: Some(CopyFormat::Json) })));
+        assert!(matches!(parse(&["show", "--copy"]), Ok(Command::Show(ShowOptions { copy: true, .. }))));
+        assert!(matches!(parse(&["show", "--sparkline"]), Ok(Command::Show(ShowOptions { sparkline: true, .. }))));
+        assert!(parse(&["get", "math", "--edit"]).is_err());
+        assert_eq!(parse(&["template", "--show"]), Ok(Command::TemplateShow));
+        assert_eq!(parse(&["get", "--tag", "Exam"]), Ok(Command::Get(Query::Tag { tag: "exam".to_string(), include_archived: false })));
+        assert_eq!(parse(&["get", "-s", "Algebra"]), Ok(Command::Get(Query::Text { text: "algebra".to_string(), sort: true, count_only: false, include_archived: false })));
+        assert_eq!(parse(&["get", "math"]), Ok(Command::Get(Query::Text { text: "math".to_string(), sort: false, count_only: false, include_archived: false })));
+        assert_eq!(parse(&["get", "fractions", "--count-only"]), Ok(Command::Get(Query::Text { text: "fractions".to_string(), sort: false, count_only: true, include_archived: false })));
+        assert_eq!(parse(&["get", "math", "--include-archived", "--count-only"]), Ok(Command::Get(Query::Text { text: "math".to_string(), sort: false, count_only: true, include_archived: true })));
+        assert!(parse(&["get", "   "]).is_err());
+        assert!(parse(&["get", "-s", ""]).is_err());
+        assert_eq!(parse(&["get", " math "]), parse(&["get", "math"]));
+        assert_eq!(parse(&["unarchive", "111"]), Ok(Command::Archive { uid: "111".to_string(), archived: false }));
+        assert!(parse(&["archive"]).is_err());
+        assert!(parse(&["get", "fractions", "--count"]).is_err());
+        assert!(parse(&["get"]).is_err());
+        assert!(parse(&["get", "-s"]).is_err());
+
+        let show = parse(&["show", "--style", "ascii", "--subject", "alg", "--reverse"]).unwrap();
+        match show {
+            Command::Show(options) => {
+                assert_eq!(options.style, TableStyle::Ascii);
+                assert_eq!(options.filter.subject.as_deref(), Some("alg"));
+                assert!(options.reverse);
+                assert!(!options.metrics);
+            }
+            command => panic!("unexpected {command:?}"),
+        }
+        assert!(parse(&["show", "--style", "fancy"]).is_err());
+        assert_eq!(parse(&["show", "-m", "--compact"]), Ok(Command::Show(cli::ShowOptions { metrics: true, compact: true, ..Default::default() })));
+        assert!(parse(&["show", "--compact"]).is_err());
+        assert!(matches!(parse(&["show", "--since-days", "7"]), Ok(Command::Show(options)) if options.since_days == Some(7)));
+        assert!(parse(&["show", "--since-days", "0"]).is_err());
+        assert!(parse(&["show", "--since-days"]).is_err());
+        assert!(matches!(parse(&["show", "--sort", "subject:desc"]), Ok(Command::Show(options)) if options.sort == stu::SortKey::parse("subject:desc")));
+        assert!(parse(&["show", "--sort", "grade"]).is_err());
+        assert!(parse(&["show", "--sort"]).is_err());
+        assert!(matches!(parse(&["show", "--porcelain"]), Ok(Command::Show(options)) if options.porcelain));
+        assert!(matches!(parse(&["show", "--missing-dates"]), Ok(Command::Show(options)) if options.filter.missing_date));
+
+        assert_eq!(parse(&["remove", "1", "2"]), Ok(Command::RemoveLogs(vec!["1".to_string(), "2".to_string()])));
+        assert_eq!(parse(&["remove", "--empty", "--force"]), Ok(Command::RemoveEmpty { force: true }));
+        assert_eq!(parse(&["add", "-j", "math", "--goal", "80%"]), Ok(Command::AddJournal { name: "math".to_string(), goal: Some(80.0) }));
+        assert_eq!(parse(&["add", "-j", "math"]), Ok(Command::AddJournal { name: "math".to_string(), goal: None }));
+        assert!(parse(&["add", "-j", "math", "--goal", "120"]).is_err());
+        assert!(parse(&["add", "-j", "math", "--goal"]).is_err());
+        assert_eq!(parse(&["add"]), Ok(Command::AddLogs { journal: None, source: LogSource::Editor, date: None, open_after_add: false }));
+        assert_eq!(parse(&["add", "math", "--date", "3/7/2025"]), Ok(Command::AddLogs { journal: Some("math".to_string()), source: LogSource::Editor, date: Some("2025-03-07".to_string()), open_after_add: false }));
+        assert_eq!(parse(&["add", "math", "--stdin"]), Ok(Command::AddLogs { journal: Some("math".to_string()), source: LogSource::Stdin, date: None, open_after_add: false }));
+        assert!(parse(&["add", "math", "--stdin", "--from-file", "rows.csv"]).is_err());
+        assert_eq!(parse(&["add", "math", "--from-uid", "111"]), Ok(Command::AddLogs { journal: Some("math".to_string()), source: LogSource::Uid("111".to_string()), date: None, open_after_add: false }));
+        assert!(parse(&["add", "math", "--from-uid", "abc"]).is_err());
+        assert_eq!(parse(&["add", "math", "--stdin", "--open-after-add"]), Ok(Command::AddLogs { journal: Some("math".to_string()), source: LogSource::Stdin, date: None, open_after_add: true }));
+        assert_eq!(parse(&["add", "--open-after-add"]), Ok(Command::AddLogs { journal: None, source: LogSource::Editor, date: None, open_after_add: true }));
+        assert_eq!(parse(&["add", "--date", "3/7/2025"]), Ok(Command::AddLogs { journal: None, source: LogSource::Editor, date: Some("2025-03-07".to_string()), open_after_add: false }));
+        assert_eq!(parse(&["add", "--stdin"]), Ok(Command::AddLogs { journal: None, source: LogSource::Stdin, date: None, open_after_add: false }));
+        assert_eq!(parse(&["add", "--stdin", "math"]), Ok(Command::AddLogs { journal: Some("math".to_string()), source: LogSource::Stdin, date: None, open_after_add: false }));
+        assert!(parse(&["add", "math", "bio"]).is_err());
+        assert!(parse(&["add", "--bogus"]).is_err());
+        assert!(parse(&["add", "math", "--date", "02/30/2025"]).is_err());
+        assert!(parse(&["add", "math", "--date", "2025-03-07"]).is_err());
+        assert!(parse(&["add", "math", "--date"]).is_err());
+        assert_eq!(parse(&["recent"]), Ok(Command::Recent(utils::DEFAULT_RECENT_COUNT)));
+        assert!(parse(&["recent", "0"]).is_err());
+        assert_eq!(parse(&["plan"]), Ok(Command::Plan(utils::DEFAULT_PLAN_COUNT)));
+        assert_eq!(parse(&["plan", "3"]), Ok(Command::Plan(3)));
+        assert!(parse(&["edit", "abc"]).is_err());
+        assert_eq!(parse(&["edit", "111", "--confirm"]), Ok(Command::EditLog { uid: "111".to_string(), note: None, confirm: true }));
+        assert_eq!(parse(&["edit", "111", "--append-note", "recheck"]), Ok(Command::EditLog { uid: "111".to_string(), note: Some("recheck".to_string()), confirm: false }));
+        assert!(parse(&["edit", "111", "--append-note"]).is_err());
+        assert!(parse(&["stats", "--by", "week"]).is_err());
+        assert_eq!(parse(&["stats", "-j", "math"]), Ok(Command::Stats { histogram: false, granularity: None, journal: Some("math".to_string()), by_topic: false }));
+        assert!(parse(&["stats", "-j"]).is_err());
+        assert_eq!(parse(&["stats", "--by-topic", "-j", "math"]), Ok(Command::Stats { histogram: false, granularity: None, journal: Some("math".to_string()), by_topic: true }));
+        assert!(parse(&["stats", "--by-topic"]).is_err());
+        assert!(parse(&["stats", "-j", "math", "--by-topic", "--histogram"]).is_err());
+        assert_eq!(parse(&["alias", "math", "remove", "m"]), Ok(Command::Alias { journal: "math".to_string(), action: AliasAction::Remove, alias: "m".to_string() }));
+        assert!(parse(&["alias", "math", "rename", "m"]).is_err());
+        assert!(parse(&["export", "-j", "math"]).is_err());
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["nope"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_journal_flag() {
+        for flag in ["-j", "--journal"] {
+            assert_eq!(parse(&["add", flag, "math"]), Ok(Command::AddJournal { name: "math".to_string(), goal: None }));
+            assert_eq!(parse(&["remove", flag, "math"]), Ok(Command::RemoveJournal("math".to_string())));
+            assert_eq!(parse(&["edit", flag, "math"]), Ok(Command::EditJournal("math".to_string())));
+            assert!(matches!(parse(&["export", flag, "math", "--json", "out.json"]), Ok(Command::Export { journal: Some(name), .. }) if name == "math"));
+
+            for args in [vec!["add", flag], vec!["remove", flag], vec!["edit", flag], vec!["stats", flag], vec!["export", flag]] {
+                assert!(parse(&args).is_err());
+            }
+            assert!(parse(&["remove", flag, " "]).is_err());
+            assert!(parse(&["stats", flag, "--histogram"]).is_err());
+            assert!(parse(&["export", flag, "--json", "out.json"]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_render_journal() {
+        colored::control::set_override(false);
+        let (_dir, path) = sample_data_file();
+        let mut journals = Vec::new();
+        stu::get_journals(&path, &mut journals).unwrap();
+
+        let wide = ShowOptions { format: TableFormat::Wide, ..ShowOptions::default() };
+        let rounded = stu::render_journal(&journals[0], &wide);
+        assert_eq!(rounded, include_str!("golden/show_rounded.txt").trim_end());
+        let markdown = stu::render_journal(&journals[0], &ShowOptions { style: TableStyle::Markdown, ..wide });
+        assert_eq!(markdown, include_str!("golden/show_markdown.txt").trim_end());
+    }
+
+    #[test]
+    fn test_read_malformed_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        std::fs::write(&path, r#"[{"name":"math","logs":[
+            {"subject":"a","topic":"b","date":"10/01/2026","uid":"1","total_questions":"20","right_answers":15,"percentage":0},
+            {"subject":"c","topic":"d","date":"10/01/2026","uid":"2","total_questions":null,"percentage":0},
+            {"subject":null,"topic":"e","date":"10/01/2026","uid":"3","total_questions":1,"right_answers":1,"percentage":0}
+        ]}]"#).unwrap();
+
+        let mut journals = Vec::new();
+        stu::get_journals(&path, &mut journals).unwrap();
+        let logs = &journals[0].logs;
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].total_questions, 20);
+        assert_eq!(logs[0].percentage, 75.0);
+        assert_eq!((logs[1].total_questions, logs[1].right_answers), (0, 0));
+
+        let count = |json: &str| serde_json::from_str::<stu::LooseValue>(json).unwrap().count();
+        assert_eq!(count(r#"" 7 ""#), Some(7));
+        assert_eq!(count("7.0"), Some(7));
+        assert_eq!(count("-1"), None);
+        assert_eq!(count(r#""seven""#), None);
+        assert_eq!(count(r#"{"n":[1,2]}"#), None);
+    }
+
+    #[test]
+    fn test_deserialize_journal() {
+        let journals: Vec<stu::Journal> = serde_json::from_str(r#"[
+            {"name":"math","logs":[{"subject":"a","topic":"b","date":"2026-10-01","uid":"1","total_questions":4,"right_answers":3}]},
+            {"name":"bio","logs":[{"subject":null}],"goal_percentage":80,"color":"green","aliases":["b"]}
+        ]"#).unwrap();
+        assert_eq!((journals[0].goal_percentage, journals[0].color.as_deref(), journals[0].aliases.len()), (None, None, 0));
+        assert_eq!(journals[0].logs[0].percentage, 0.0);
+        assert_eq!((journals[1].goal_percentage, journals[1].color.as_deref(), journals[1].logs.len()), (Some(80.0), Some("green"), 0));
+        assert!(serde_json::from_str::<Vec<stu::Journal>>(r#"[{"name":"math"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_study_plan() {
+        let log = |topic: &str, date: &str, right: usize| format!(r#"{{"subject":"s","topic":"{topic}","date":"{date}","uid":"{date}{topic}","total_questions":100,"right_answers":{right},"percentage":{right}}}"#);
+        let journals: Vec<stu::Journal> = serde_json::from_str(&format!(r#"[{{"name":"math","logs":[{},{},{},{}]}},{{"name":"bio","logs":[{}]}}]"#,
+            log("limits", "2026-09-01", 10), log("Limits", "2026-10-03", 45), log("derivatives", "2026-10-14", 90), log("series", "2026-09-01", 100), log("cells", "2026-10-15", 60))).unwrap();
+        let today = utils::days_from_civil(2026, 10, 15);
+
+        let plan = stu::study_plan(&journals, today, 5);
+        let ranked: Vec<(&str, &str, f32, i64)> = plan.iter().map(|x| (x.journal.as_str(), x.topic.as_str(), x.percentage, x.days_since)).collect();
+        assert_eq!(ranked, vec![("math", "Limits", 45.0, 12), ("bio", "cells", 60.0, 0), ("math", "derivatives", 90.0, 1)]);
+        assert_eq!(stu::study_plan(&journals, today, 1).len(), 1);
+        assert_eq!(messages::days_ago(12), "12 days ago");
+    }
+
+    #[test]
+    fn test_stats_by_topic() {
+        let journals: Vec<stu::Journal> = serde_json::from_str(r#"[{"name":"math","logs":[
+            {"subject":"calculus","topic":"limits, series","date":"2026-10-01","uid":"1","total_questions":10,"right_answers":8},
+            {"subject":"calculus","topic":"Limits","date":"2026-10-02","uid":"2","total_questions":10,"right_answers":4},
+            {"subject":"calculus","topic":"derivatives","date":"2026-10-03","uid":"3","total_questions":4,"right_answers":3},
+            {"subject":"calculus","topic":"proofs","date":"2026-10-04","uid":"4","total_questions":0,"right_answers":0}]}]"#).unwrap();
+
+        let stats = stu::stats_by_topic(&journals[0]);
+        let rows: Vec<(&str, usize, usize, f32)> = stats.iter().map(|x| (x.topic.as_str(), x.logs, x.total_questions, x.percentage)).collect();
+        assert_eq!(rows, vec![("limits", 2, 20, 60.0), ("derivatives", 1, 4, 75.0), ("series", 1, 10, 80.0)]);
+        assert!(stu::stats_by_topic(&stu::Journal::new("bio")).is_empty());
+    }
+
+    #[test]
+    fn test_read_topics() {
+        let (_dir, path) = sample_data_file();
+        std::fs::write(&path, r#"{"schema":2,"journals":[{"name":"math","logs":[{"subject":"calculus","topic":"limits, Derivatives,","date":"2026-10-01","uid":"1","total_questions":4,"right_answers":3}]}]}"#).unwrap();
+
+        let store = stu::JournalStore::load(&path).unwrap();
+        let log = &store.journals[0].logs[0];
+        assert_eq!(log.topics, vec!["limits", "Derivatives"]);
+        assert_eq!(log.topic_label(), "limits, Derivatives");
+        assert!(log.has_topic("derivatives") && !log.has_topic("limits, derivatives"));
+        assert_eq!(read_data(&path)[0]["logs"][0]["topics"], serde_json::json!(["limits", "Derivatives"]));
+
+        let journals: Vec<stu::Journal> = serde_json::from_str(r#"[{"name":"math","logs":[{"subject":"s","topics":["a"," b ",""],"date":"2026-10-01","uid":"1"}]}]"#).unwrap();
+        assert_eq!(journals[0].logs[0].topics, vec!["a", "b"]);
+        assert_eq!(utils::parse_topics(" , "), Vec::<String>::new());
+
+        let plan = stu::study_plan(&store.journals, utils::days_from_civil(2026, 10, 15), 5);
+        assert_eq!(plan.iter().map(|x| x.topic.as_str()).collect::<Vec<_>>(), vec!["limits", "Derivatives"]);
+    }
+
+    #[test]
+    fn test_read_large_data_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.json");
+        let journals: Vec<String> = (0..50).map(|j| {
+            let logs: Vec<String> = (0..2000).map(|l| format!(r#"{{"subject":"s{l}","topic":"t","date":"2026-10-01","uid":"{j}-{l}","total_questions":4,"right_answers":{},"percentage":0}}"#, l % 5)).collect();
+            format!(r#"{{"name":"j{j}","logs":[{}]}}"#, logs.join(","))
+        }).collect();
+        std::fs::write(&path, format!(r#"{{"schema":2,"journals":[{}]}}"#, journals.join(","))).unwrap();
+
+        let before = ALLOCATIONS.with(Cell::get);
+        let journals = stu::read_journals(&path).unwrap();
+        let allocations = ALLOCATIONS.with(Cell::get) - before;
+        assert_eq!(journals.len(), 50);
+        // A log owns about 5 strings and vectors, building a `Value` for each
+        // one on the way took more than twice that
+        assert!(allocations < 100_000 * 10, "{allocations} allocations");
+        assert_eq!(journals.iter().map(|x| x.logs.len()).sum::<usize>(), 100_000);
+        assert_eq!(journals[49].logs[1999].uid, "49-1999");
+        assert_eq!(journals[49].logs[1999].percentage, 100.0);
+        assert_eq!(journals[0].logs[2].percentage, 50.0);
+    }
+
+    #[test]
+    fn test_stored_or_computed_percentage() {
+        let mut log: stu::Log = serde_json::from_str(r#"{"subject":"a","topic":"b","date":"2026-10-01","uid":"1","total_questions":4,"right_answers":3,"percentage":90}"#).unwrap();
+        assert_eq!(stu::stored_or_computed_percentage(&log, false), 75.0);
+        assert_eq!(stu::stored_or_computed_percentage(&log, true), 90.0);
+        log.percentage = 0.0;
+        assert_eq!(stu::stored_or_computed_percentage(&log, true), 75.0);
+        log.total_questions = 0;
+        assert_eq!(stu::stored_or_computed_percentage(&log, true), 0.0);
+    }
+
+    #[test]
+    fn test_migrate_legacy_data() {
+        let (dir, path) = sample_data_file();
+
+        let store = stu::JournalStore::load(&path).unwrap();
+        assert_eq!(store.journals.len(), 2);
+        assert_eq!(read_data(&path)[0]["logs"].as_array().unwrap().len(), 2);
+        assert_eq!(read_data(&path)[0]["logs"][0]["date"], "2026-10-01");
+        let backup = dir.path().join("data.json.schema0.bak");
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), SAMPLE_DATA);
+
+        let other = dir.path().join("other.json").display().to_string();
+        std::fs::write(&other, SAMPLE_DATA).unwrap();
+        assert_eq!(stu::read_journals(&other).unwrap().len(), 2);
+        assert_eq!(std::fs::read_to_string(&other).unwrap(), SAMPLE_DATA);
+
+        std::fs::write(&other, r#"{"schema":99,"journals":[]}"#).unwrap();
+        assert!(stu::read_journals(&other).is_err());
+        std::fs::write(&other, r#"{"journals":[]}"#).unwrap();
+        assert!(stu::read_journals(&other).is_err());
+    }
+
+    #[test]
+    fn test_run_import_and_remove() {
+        let (dir, path) = sample_data_file();
+        let rows = dir.path().join("rows.csv").display().to_string();
+        std::fs::write(&rows, "calculus,limits,10,8\n").unwrap();
+
+        run(&["add", "math", "--from-file", &rows, "--date", "09/15/2026"], &path).unwrap();
+        let data = read_data(&path);
+        let logs = data[0]["logs"].as_array().unwrap();
+        assert_eq!(logs.len(), 3);
+        assert_eq!(logs[2]["subject"], "calculus");
+        assert_eq!(logs[2]["percentage"], 80.0);
+        assert_eq!(logs[2]["date"], "2026-09-15");
+
+        run(&["remove", "111"], &path).unwrap();
+        let data = read_data(&path);
+        let uids: Vec<&str> = data[0]["logs"].as_array().unwrap().iter().map(|x| x["uid"].as_str().unwrap()).collect();
+        assert_eq!(uids.len(), 2);
+        assert!(!uids.contains(&"111"));
+
+        run(&["remove", "-j", "bio"], &path).unwrap();
+        assert_eq!(read_data(&path).as_array().unwrap().len(), 1);
+
+        assert!(run(&["remove", "999"], &path).is_err());
+        assert!(run(&["nope"], &path).is_err());
+        assert!(run(&[], &path).is_err());
+        assert!(run(&["add"], &path).is_err());
+    }
+
+    #[test]
+    fn test_run_import_uses_tag_rules_next_to_data_file() {
+        let (dir, path) = sample_data_file();
+        let rows = dir.path().join("rows.csv").display().to_string();
+        std::fs::write(&rows, "calculus,limits,10,8\n").unwrap();
+        std::fs::write(TagRules::path(dir.path()), "limits = \"math\"\n").unwrap();
+
+        run(&["add", "math", "--from-file", &rows], &path).unwrap();
+        let data = read_data(&path);
+        let logs = data[0]["logs"].as_array().unwrap();
+        assert_eq!(logs[2]["tags"], serde_json::json!(["math"]));
+    }
+
+    #[test]
+    fn test_run_add_default_journal() {
+        let (dir, path) = sample_data_file();
+        let rows = dir.path().join("rows.csv").display().to_string();
+        std::fs::write(&rows, "calculus,limits,10,8\n").unwrap();
+        let config = Config { data_path: path.to_path_buf(), default_journal: Some("bio".to_string()), ..Config::default() };
+        let run = |args: &[&str]| cli::run(args.iter().map(|x| x.to_string()).collect(), &config, &path);
+
+        run(&["add", "--from-file", &rows, "--date", "09/15/2026"]).unwrap();
+        assert_eq!(read_data(&path)[1]["logs"][0]["date"], "2026-09-15");
+        run(&["add", "math", "--from-file", &rows]).unwrap();
+        assert_eq!(read_data(&path)[0]["logs"].as_array().unwrap().len(), 3);
+        assert_eq!(read_data(&path)[1]["logs"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_run_remove_some_missing() {
+        let (_dir, path) = sample_data_file();
+        assert!(run(&["remove", "999", "888"], &path).is_err());
+        assert_eq!(read_data(&path)[0]["logs"].as_array().unwrap().len(), 2);
+
+        run(&["remove", "999", "111", "111"], &path).unwrap();
+        let logs = read_data(&path)[0]["logs"].clone();
+        assert_eq!(logs.as_array().unwrap().len(), 1);
+        assert_eq!(logs[0]["uid"], "222");
+    }
+
+    #[test]
+    fn test_load_without_percentages() {
+        let (_dir, path) = sample_data_file();
+        std::fs::write(&path, r#"{"schema":3,"journals":[{"name":"math","logs":[{"subject":"a","topics":["b"],"date":"2026-10-01","uid":"1","total_questions":4,"right_answers":3,"percentage":12}]},{"name":"bio","logs":[]}]}"#).unwrap();
+
+        assert_eq!(stu::JournalStore::load_without_percentages(&path).unwrap().journals[0].logs[0].percentage, 12.0);
+        assert_eq!(stu::JournalStore::load(&path).unwrap().journals[0].logs[0].percentage, 75.0);
+        run(&["remove", "-j", "bio"], &path).unwrap();
+        assert_eq!(read_data(&path)[0]["logs"][0]["percentage"], 12.0);
+    }
+
+    #[test]
+    fn test_restore_backup() {
+        let (dir, path) = sample_data_file();
+        assert_eq!(stu::newest_backup(&path), None);
+
+        let old = stu::backup_data(&path, "old").unwrap();
+        std::fs::write(dir.path().join("data.json.broken.bak"), "[{\"name\":").unwrap();
+        std::fs::write(&path, "{\"schema\":2,\"journals\":[{\"na").unwrap();
+        assert_eq!(stu::newest_backup(&path), Some(old.clone()));
+
+        let damaged = stu::restore_backup(&path, &old).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), SAMPLE_DATA);
+        assert!(std::fs::read_to_string(damaged).unwrap().ends_with("[{\"na"));
+        assert_eq!(stu::read_journals(&path).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_run_archive() {
+        let (dir, path) = sample_data_file();
+
+        run(&["archive", "111"], &path).unwrap();
+        assert_eq!(read_data(&path)[0]["logs"][0]["archived"], true);
+        assert!(read_data(&path)[0]["logs"][1].get("archived").is_none());
+
+        let mut journals = stu::read_journals(&path).unwrap();
+        let mut all = stu::read_journals(&path).unwrap();
+        stu::hide_archived(&mut journals, &ShowOptions::default());
+        assert_eq!(journals[0].logs.len(), 1);
+        stu::hide_archived(&mut all, &ShowOptions { include_archived: true, ..ShowOptions::default() });
+        assert_eq!(all[0].logs.len(), 2);
+        let out = dir.path().join("out.json").display().to_string();
+        run(&["export", "--json", &out], &path).unwrap();
+        assert!(std::fs::read_to_string(&out).unwrap().contains("\"archived\":true"));
+
+        run(&["unarchive", "111"], &path).unwrap();
+        assert!(read_data(&path)[0]["logs"][0].get("archived").is_none());
+        assert!(run(&["archive", "999"], &path).is_err());
+    }
 
+    #[test]
+    fn test_edit_keeps_archived() {
+        let (_dir, path) = sample_data_file();
+        run(&["archive", "111"], &path).unwrap();
+        let journals = stu::read_journals(&path).unwrap();
+        let archived = &journals[0].logs[0];
+
+        let log = stu::edited_log(filled_template("20", "16"), archived).unwrap();
+        assert!(log.archived && log.right_answers == 16);
+        let logs = stu::logs_from_tf(format!("UID: 111\n{}\n---\nUID: 222\n{}", filled_template("20", "16"), filled_template("10", "8")), &journals[0].logs).unwrap();
+        assert_eq!(logs.iter().map(|x| x.archived).collect::<Vec<_>>(), vec![true, false]);
+    }
+
+    #[test]
+    fn test_run_alias() {
+        let (dir, path) = sample_data_file();
+        let rows = dir.path().join("rows.csv").display().to_string();
+        std::fs::write(&rows, "calculus,limits,10,8\n").unwrap();
+
+        run(&["alias", "math", "add", "m"], &path).unwrap();
+        run(&["alias", "bio", "add", "b"], &path).unwrap();
+        assert_eq!(read_data(&path)[0]["aliases"], serde_json::json!(["m"]));
+        run(&["alias", "bio", "remove", "b"], &path).unwrap();
+        assert!(read_data(&path)[1].get("aliases").is_none());
+        assert!(run(&["alias", "bio", "remove", "b"], &path).is_err());
+
+        run(&["add", "m", "--from-file", &rows], &path).unwrap();
+        assert_eq!(read_data(&path)[0]["logs"].as_array().unwrap().len(), 3);
+        run(&["get", "m"], &path).unwrap();
+
+        assert!(run(&["alias", "bio", "add", "m"], &path).is_err());
+        assert!(run(&["alias", "bio", "add", "math"], &path).is_err());
+        let journals = stu::read_journals(&path).unwrap();
+        assert!(stu::name_taken(&journals, "m"));
+        assert!(stu::name_taken(&journals, "bio"));
+        assert!(!stu::name_taken(&journals, "chemistry"));
+        assert!(run(&["add", "-j", "m"], &path).is_err());
+
+        run(&["remove", "-j", "m"], &path).unwrap();
+        assert_eq!(read_data(&path).as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_duplicate_journals() {
+        let (_dir, path) = sample_data_file();
+        std::fs::write(&path, r#"[{"name":"math","logs":[]},{"name":"bio","logs":[]},{"name":"math","logs":[]}]"#).unwrap();
+
+        let store = stu::JournalStore::load(&path).unwrap();
+        assert_eq!(stu::duplicate_journal_names(&store.journals), vec!["math".to_string()]);
+
+        assert!(run(&["remove", "-j", "math"], &path).is_err());
+        assert_eq!(read_data(&path).as_array().unwrap().len(), 3);
+        run(&["remove", "-j", "bio"], &path).unwrap();
+        assert_eq!(read_data(&path).as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_add_existing_journal() {
+        let (_dir, path) = sample_data_file();
+
+        assert!(stu::name_taken(&stu::read_journals(&path).unwrap(), "math"));
+        assert!(run(&["add", "-j", "math"], &path).is_err());
+        assert!(stu::duplicate_journal_names(&stu::read_journals(&path).unwrap()).is_empty());
+        assert_eq!(read_data(&path).as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_run_rename_subject() {
+        let (_dir, path) = sample_data_file();
+
+        run(&["rename-subject", "ALGEBRA", "linear algebra"], &path).unwrap();
+        run(&["rename-subject", "Triangles", "polygons", "--topic"], &path).unwrap();
+        let data = read_data(&path);
+        assert_eq!(data[0]["logs"][0]["subject"], "linear algebra");
+        assert_eq!(data[0]["logs"][1]["subject"], "geometry");
+        assert_eq!(data[0]["logs"][1]["topics"], serde_json::json!(["polygons"]));
+
+        assert!(run(&["rename-subject", "chemistry", "biology"], &path).is_err());
+        assert!(parse(&["rename-subject", "algebra"]).is_err());
+        assert!(parse(&["rename-subject", "algebra", " "]).is_err());
+    }
+
+    #[test]
+    fn test_run_merge_logs() {
+        let (_dir, path) = sample_data_file();
+
+        assert!(run(&["merge-logs", "222", "111"], &path).is_err());
+        assert!(run(&["merge-logs", "222", "999", "--force"], &path).is_err());
+        run(&["merge-logs", "222", "111", "--force"], &path).unwrap();
+
+        let data = read_data(&path);
+        let logs = data[0]["logs"].as_array().unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0]["uid"], "222");
+        assert_eq!(logs[0]["date"], "2026-10-01");
+        assert_eq!((logs[0]["total_questions"].as_u64(), logs[0]["right_answers"].as_u64()), (Some(30), Some(24)));
+        assert_eq!(logs[0]["percentage"], 80.0);
+
+        assert!(parse(&["merge-logs", "111"]).is_err());
+        assert!(parse(&["merge-logs", "111", "111"]).is_err());
+        assert!(parse(&["merge-logs", "111", "222", "333"]).is_err());
+    }
+
+    #[test]
+    fn test_run_show_out() {
+        let (dir, path) = sample_data_file();
+        let report = dir.path().join("report.txt").display().to_string();
+
+        run(&["show", "--subject", "geo", "--out", &report], &path).unwrap();
+        let content = std::fs::read_to_string(&report).unwrap();
+        assert!(content.contains("triangles"));
+        assert!(!content.contains("fractions"));
+        assert!(!content.contains('\x1b'));
+
+        std::fs::write(&report, "kept").unwrap();
+        assert!(run(&["show", "--subject", "chemistry", "--out", &report], &path).is_err());
+        run(&["show", "--missing-dates", "--out", &report], &path).unwrap();
+        assert_eq!(std::fs::read_to_string(&report).unwrap(), "kept");
+
+        assert!(parse(&["show", "--out"]).is_err());
+    }
 }
 
-pub mod stu {
-    pub mod utils;
-}
\ No newline at end of file
+pub mod stu;
\ No newline at end of file